@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use oui_lookup::oui::Oui;
+
+fuzz_target!(|line: &str| {
+    if let Some(oui) = Oui::from_manuf(line) {
+        // anything that parses has to round-trip its prefix
+        let prefix = oui.mac_prefix.to_string();
+        assert!(oui_lookup::oui::MacPrefix::parse(&prefix).is_some(), "{prefix}");
+    }
+    let _ = Oui::from_wka(line);
+});
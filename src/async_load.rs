@@ -0,0 +1,189 @@
+//! Async database loading for use inside a tokio runtime, enabled by the `async` feature.
+
+use std::path::Path;
+
+use anyhow::Context as _;
+use reqwest::header;
+use reqwest::{Client, StatusCode};
+use serif::macros::*;
+
+use crate::cache::{self, Cache, USER_AGENT, create_private_dir, create_private_tempfile};
+use crate::database::OuiDatabase;
+
+/// Load the database without blocking the runtime, using and updating a cache file if given.
+///
+/// This follows the same rules as the command line tool: a cached copy is revalidated with a
+/// conditional HEAD request and re-downloaded if it's stale, and failing to read or write the cache
+/// isn't fatal. Network IO is async, and decoding, parsing, and saving the database run on tokio's
+/// blocking thread pool so that they don't stall the runtime.
+pub async fn load_async(url: &str, cache_path: Option<&Path>) -> anyhow::Result<OuiDatabase> {
+    let client =
+        Client::builder().user_agent(USER_AGENT).build().context("failed to create HTTP client")?;
+    let Some(cache_path) = cache_path else {
+        return Ok(download(&client, url).await?.db);
+    };
+
+    match read_cache(cache_path).await {
+        Ok(Some(cache)) if up_to_date(&client, url, &cache).await => {
+            info!("cache is up to date");
+            return Ok(cache.db);
+        }
+        Ok(Some(_)) => info!("cache is stale, re-downloading"),
+        Ok(None) => debug!("cache file {} doesn't exist", cache_path.display()),
+        Err(err) => warn!("error loading cache: {err:#}"),
+    }
+
+    let cache = download(&client, url).await?;
+    let path = cache_path.to_owned();
+    let (cache, result) = tokio::task::spawn_blocking(move || {
+        let result = write_cache(&cache, &path);
+        (cache, result)
+    })
+    .await
+    .context("cache file writer panicked")?;
+    if let Err(err) = result {
+        warn!("failed to save cache file: {err:#}");
+    }
+    Ok(cache.db)
+}
+
+async fn read_cache(path: &Path) -> anyhow::Result<Option<Cache>> {
+    debug!("loading cache file {}", path.display());
+    match tokio::fs::read(path).await {
+        Ok(bytes) => {
+            let cache = tokio::task::spawn_blocking(move || Cache::from_bytes(&bytes))
+                .await
+                .context("cache file parser panicked")??;
+            Ok(Some(cache))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).context(format!("failed to open {} for reading", path.display())),
+    }
+}
+
+/// Write a cache file, owner-only on Unix and replaced all at once like the CLI does
+fn write_cache(cache: &Cache, path: &Path) -> anyhow::Result<()> {
+    create_private_dir(path.parent().unwrap()).context("failed to create cache directory")?;
+    let mut tmp = create_private_tempfile(path).context("failed to open cache file for writing")?;
+    postcard::to_io(cache, tmp.as_file_mut()).context("failed writing cache file")?;
+    tmp.persist(path).map_err(|err| err.error).context("failed to replace cache file")?;
+    Ok(())
+}
+
+async fn up_to_date(client: &Client, url: &str, cache: &Cache) -> bool {
+    let mut req = client.head(url);
+    if let Some(ref val) = cache.last_modified {
+        req = req.header(header::IF_MODIFIED_SINCE, val);
+    }
+    if let Some(ref val) = cache.etag {
+        req = req.header(header::IF_NONE_MATCH, val);
+    }
+
+    match req.send().await {
+        Ok(resp) => resp.status() == StatusCode::NOT_MODIFIED,
+        Err(err) => {
+            warn!("failed to send HEAD request: {err}");
+            false
+        }
+    }
+}
+
+async fn download(client: &Client, url: &str) -> anyhow::Result<Cache> {
+    let resp = client.get(url).send().await.context("failed to send web request")?;
+    if !resp.status().is_success() {
+        anyhow::bail!("web request to fetch database failed: {}", resp.status());
+    }
+    let last_modified = cache::header_string(resp.headers(), header::LAST_MODIFIED);
+    let etag = cache::header_string(resp.headers(), header::ETAG);
+    let gz_data = resp.bytes().await.context("failed to get web request response body")?;
+    tokio::task::spawn_blocking(move || Cache::from_manuf_gz(&gz_data, last_modified, etag))
+        .await
+        .context("database parser panicked")?
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use flate2::write::GzEncoder;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    const MANUF: &str = "\
+00:50:F1\tMaxlinear\tMaxlinear, Inc
+00:55:DA:50/28\tNanoleaf\tNanoleaf
+";
+
+    /// Serve a gzipped manuf file with an ETag, counting the GET requests. HEAD requests with a
+    /// matching If-None-Match get a 304.
+    async fn mock_server() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/manuf.gz", listener.local_addr().unwrap());
+        let gets = Arc::new(AtomicUsize::new(0));
+
+        let mut gz = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(MANUF.as_bytes()).unwrap();
+        let body = gz.finish().unwrap();
+
+        let counter = Arc::clone(&gets);
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut req = Vec::new();
+                while !req.ends_with(b"\r\n\r\n") {
+                    let mut buf = [0u8; 1024];
+                    let n = stream.read(&mut buf).await.unwrap();
+                    assert!(n != 0, "connection closed mid-request");
+                    req.extend_from_slice(&buf[..n]);
+                }
+                let req = String::from_utf8(req).unwrap().to_ascii_lowercase();
+
+                let mut resp = Vec::new();
+                if req.starts_with("head") && req.contains("if-none-match: \"v1\"") {
+                    resp.extend_from_slice(
+                        b"HTTP/1.1 304 Not Modified\r\nconnection: close\r\n\r\n",
+                    );
+                } else {
+                    let head = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-length: {}\r\netag: \"v1\"\r\n\
+                         connection: close\r\n\r\n",
+                        body.len()
+                    );
+                    resp.extend_from_slice(head.as_bytes());
+                    if req.starts_with("get") {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        resp.extend_from_slice(&body);
+                    }
+                }
+                stream.write_all(&resp).await.unwrap();
+            }
+        });
+        (url, gets)
+    }
+
+    #[tokio::test]
+    async fn test_load_async() {
+        let (url, gets) = mock_server().await;
+        let tmp = tempfile::tempdir().unwrap();
+        let cache_path = tmp.path().join("oui-lookup").join("manuf.db");
+
+        let db = load_async(&url, Some(&cache_path)).await.unwrap();
+        assert_eq!(db.len(), 2);
+        assert_eq!(gets.load(Ordering::SeqCst), 1);
+        let cache = Cache::from_bytes(&std::fs::read(&cache_path).unwrap()).unwrap();
+        assert_eq!(cache.etag.as_deref(), Some("\"v1\""));
+
+        // revalidated rather than downloaded again
+        let db = load_async(&url, Some(&cache_path)).await.unwrap();
+        assert_eq!(db.len(), 2);
+        assert_eq!(gets.load(Ordering::SeqCst), 1);
+
+        let db = load_async(&url, None).await.unwrap();
+        assert_eq!(db.len(), 2);
+        assert_eq!(gets.load(Ordering::SeqCst), 2);
+    }
+}
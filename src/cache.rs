@@ -0,0 +1,420 @@
+//! The on-disk cache format for the database, along with the HTTP validators for revalidating it.
+
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use flate2::bufread::GzDecoder;
+use reqwest::header::{AsHeaderName, HeaderMap};
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+
+use crate::database::OuiDatabase;
+
+/// Where the gzipped manuf database is downloaded from
+pub const DB_URL: &str = "https://www.wireshark.org/download/automated/data/manuf.gz";
+
+/// Where the IEEE MA-L registry CSV is downloaded from
+pub const IEEE_CSV_URL: &str = "https://standards-oui.ieee.org/oui/oui.csv";
+
+/// Where the IEEE MA-M registry CSV is downloaded from
+pub const IEEE_MAM_CSV_URL: &str = "https://standards-oui.ieee.org/oui28/mam.csv";
+
+/// Where the IEEE MA-S registry CSV is downloaded from
+pub const IEEE_MAS_CSV_URL: &str = "https://standards-oui.ieee.org/oui36/oui36.csv";
+
+/// User-Agent sent when downloading the database, identifying this tool and its version
+pub const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// The first two bytes of any gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+static DEFAULT_CACHE: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    let mut path = dirs::cache_dir()?;
+    path.push("oui-lookup");
+    path.push("manuf.db");
+    Some(path)
+});
+
+/// Get the platform-dependent default cache file location, if there is one
+pub fn default_cache_path<'a>() -> Option<&'a Path> {
+    DEFAULT_CACHE.as_deref()
+}
+
+/// Create a directory (and any missing parents), owner-only on Unix.
+///
+/// Existing directories are left alone, since a custom cache file could live anywhere.
+pub fn create_private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(dir)
+}
+
+/// Create a uniquely named temp file next to `path`, owner-only on Unix, to be renamed over it.
+///
+/// The unique name keeps concurrent runs from writing the same temp file, and renaming replaces a
+/// cache file created by an older version with the default umask.
+pub fn create_private_tempfile(path: &Path) -> io::Result<NamedTempFile> {
+    let mut prefix = path.file_name().unwrap_or_default().to_owned();
+    prefix.push(".");
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(&prefix).suffix(".part");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(std::fs::Permissions::from_mode(0o600));
+    }
+    builder.tempfile_in(path.parent().unwrap_or(Path::new(".")))
+}
+
+/// Get a response header's value, if it's present and valid text
+pub fn header_string(headers: &HeaderMap, name: impl AsHeaderName) -> Option<String> {
+    headers.get(name).and_then(|v| v.to_str().ok()).map(String::from)
+}
+
+/// The kinds of database file which can be downloaded and cached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadFormat {
+    /// Wireshark's gzipped manuf file
+    ManufGz,
+    /// The IEEE registry CSV, which is served uncompressed
+    IeeeCsv,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Cache {
+    /// Value of the `Last-Modified` header when the database was downloaded
+    pub last_modified: Option<String>,
+    /// Value of the `ETag` header when the database was downloaded
+    pub etag: Option<String>,
+    pub db: OuiDatabase,
+    /// Generation date claimed by the manuf file's header comments
+    pub generated: Option<String>,
+    /// Hash of the decompressed manuf file, see [`content_hash`]
+    pub content_hash: Option<u64>,
+}
+
+/// Layout of cache files written before the `content_hash` field was added
+#[derive(Deserialize)]
+struct CacheV1 {
+    last_modified: Option<String>,
+    etag: Option<String>,
+    db: OuiDatabase,
+    generated: Option<String>,
+}
+
+impl From<CacheV1> for Cache {
+    fn from(old: CacheV1) -> Self {
+        Self {
+            last_modified: old.last_modified,
+            etag: old.etag,
+            db: old.db,
+            generated: old.generated,
+            content_hash: None,
+        }
+    }
+}
+
+/// Layout of cache files written before the `generated` field was added.
+///
+/// Postcard isn't self-describing, so `#[serde(default)]` can't fill in a missing trailing field.
+#[derive(Deserialize)]
+struct CacheV0 {
+    last_modified: Option<String>,
+    etag: Option<String>,
+    db: OuiDatabase,
+}
+
+impl From<CacheV0> for Cache {
+    fn from(old: CacheV0) -> Self {
+        Self {
+            last_modified: old.last_modified,
+            etag: old.etag,
+            db: old.db,
+            generated: None,
+            content_hash: None,
+        }
+    }
+}
+
+impl Cache {
+    /// Decode the contents of a cache file, including ones written by older versions.
+    ///
+    /// The postcard data may be plain or gzip-compressed, which is detected by gzip's magic
+    /// bytes. A plain cache can never start with them because its first byte is an `Option` tag.
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let decompressed;
+        let bytes = if bytes.starts_with(&GZIP_MAGIC) {
+            let mut data = Vec::with_capacity(bytes.len() * 4);
+            GzDecoder::new(bytes)
+                .read_to_end(&mut data)
+                .context("failed to decompress cache file")?;
+            decompressed = data;
+            &decompressed
+        } else {
+            bytes
+        };
+        postcard::from_bytes::<Self>(bytes)
+            .or_else(|_| postcard::from_bytes::<CacheV1>(bytes).map(Self::from))
+            .or_else(|_| postcard::from_bytes::<CacheV0>(bytes).map(Self::from))
+            .context("failed to parse cache file")
+    }
+
+    /// Build a cache from a freshly downloaded gzipped manuf file and its response headers
+    pub fn from_manuf_gz(
+        gz_data: &[u8],
+        last_modified: Option<String>,
+        etag: Option<String>,
+    ) -> anyhow::Result<Self> {
+        Self::from_download(gz_data, DownloadFormat::ManufGz, last_modified, etag)
+    }
+
+    /// Build a cache from a freshly downloaded database file and its response headers.
+    ///
+    /// Mailing addresses from the IEEE CSV aren't kept in cache files, so they're only available
+    /// right after downloading.
+    pub fn from_download(
+        body: &[u8],
+        format: DownloadFormat,
+        last_modified: Option<String>,
+        etag: Option<String>,
+    ) -> anyhow::Result<Self> {
+        let data = decode_download(body, format)?;
+        Self::from_data(data, format, last_modified, etag)
+    }
+
+    /// Like [`from_download`](Self::from_download), but when the file is the same one this cache
+    /// was built from, keep the already parsed database and only update the validators.
+    ///
+    /// Servers don't always keep `ETag` and `Last-Modified` stable for unchanged content, and
+    /// parsing and sorting the whole file again for nothing is the slowest part of a refresh.
+    pub fn refresh(
+        self,
+        body: &[u8],
+        format: DownloadFormat,
+        last_modified: Option<String>,
+        etag: Option<String>,
+    ) -> anyhow::Result<Self> {
+        let data = decode_download(body, format)?;
+        if self.content_hash == Some(content_hash(&data)) {
+            return Ok(Self { last_modified, etag, ..self });
+        }
+        Self::from_data(data, format, last_modified, etag)
+    }
+
+    fn from_data(
+        data: Vec<u8>,
+        format: DownloadFormat,
+        last_modified: Option<String>,
+        etag: Option<String>,
+    ) -> anyhow::Result<Self> {
+        let content_hash = Some(content_hash(&data));
+        let str_data = String::from_utf8(data).context("OUI db data is not UTF-8")?;
+        let (db, generated) = match format {
+            DownloadFormat::ManufGz => {
+                (OuiDatabase::from_manuf(&str_data), manuf_generated_date(&str_data))
+            }
+            DownloadFormat::IeeeCsv => (OuiDatabase::from_ieee_csv(&str_data), None),
+        };
+
+        Ok(Self { last_modified, etag, db, generated, content_hash })
+    }
+
+    /// Parse the `Last-Modified` header value, returning `None` if it's missing or malformed
+    pub fn last_modified_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_http_date(self.last_modified.as_deref()?)
+    }
+}
+
+/// Parse an HTTP date like `Sun, 15 Jun 2025 04:12:33 GMT`, as used by `Last-Modified`
+pub fn parse_http_date(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(s).ok().map(|dt| dt.to_utc())
+}
+
+/// Get the plain text of a downloaded database file
+fn decode_download(body: &[u8], format: DownloadFormat) -> anyhow::Result<Vec<u8>> {
+    match format {
+        DownloadFormat::ManufGz => {
+            let mut data = Vec::with_capacity(body.len() * 4);
+            GzDecoder::new(body).read_to_end(&mut data).context("failed to decode gzipped data")?;
+            Ok(data)
+        }
+        // any Content-Encoding compression was already undone by the HTTP client
+        DownloadFormat::IeeeCsv => Ok(body.to_vec()),
+    }
+}
+
+/// Hash a decompressed manuf file to tell whether a download changed anything.
+///
+/// This is 64-bit FNV-1a, which unlike std's hashers is stable between builds. The crate version
+/// is hashed first, so that a cache parsed by a different version of the parser is never reused.
+fn content_hash(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let version = env!("CARGO_PKG_VERSION").as_bytes();
+    version
+        .iter()
+        .chain(&[0])
+        .chain(data)
+        .fold(OFFSET_BASIS, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(PRIME))
+}
+
+/// Find the generation date in the comment block at the top of a manuf file.
+///
+/// This looks for a `# Generated: <date>` (or `Generated on:`/`Date:`) comment before the first
+/// entry, and returns the date text as-is.
+fn manuf_generated_date(data: &str) -> Option<String> {
+    for line in data.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        // stop at the end of the header block
+        let comment = line.strip_prefix('#')?;
+        let Some((key, value)) = comment.split_once(':') else { continue };
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim();
+        if matches!(key.as_str(), "generated" | "generated on" | "date") && !value.is_empty() {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::oui::Oui;
+
+    #[test]
+    fn test_last_modified_datetime() {
+        let mut cache = Cache {
+            last_modified: None,
+            etag: None,
+            db: OuiDatabase::default(),
+            generated: None,
+            content_hash: None,
+        };
+        assert_eq!(cache.last_modified_datetime(), None);
+
+        cache.last_modified = Some(String::from("Sun, 15 Jun 2025 04:12:33 GMT"));
+        let dt = cache.last_modified_datetime().unwrap();
+        assert_eq!(dt.to_rfc3339(), "2025-06-15T04:12:33+00:00");
+
+        cache.last_modified = Some(String::from("last tuesday"));
+        assert_eq!(cache.last_modified_datetime(), None);
+    }
+
+    #[test]
+    fn test_manuf_generated_date() {
+        let data = "\
+# This file was generated by running ./tools/make-manuf.py.
+# Don't change it directly, change manuf.tmpl instead.
+#
+# Source: https://www.wireshark.org/download/automated/data/manuf
+# Generated: 2025-06-01 04:12:33 UTC
+#
+
+00:00:00\t00:00:00\tOfficially Xerox, but 0:0:0:0:0:0 is more common
+# Date: not part of the header
+";
+        assert_eq!(manuf_generated_date(data).as_deref(), Some("2025-06-01 04:12:33 UTC"));
+
+        let data = "# some header\n00:00:00\tXerox\tXerox Corporation\n# Date: too late\n";
+        assert_eq!(manuf_generated_date(data), None);
+    }
+
+    #[test]
+    fn test_load_old_cache() {
+        #[derive(Serialize)]
+        struct OldCache<'a> {
+            last_modified: Option<&'a str>,
+            etag: Option<&'a str>,
+            db: Vec<Oui>,
+        }
+
+        let oui = Oui::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc").unwrap();
+        let old = OldCache { last_modified: None, etag: Some("\"abc\""), db: vec![oui] };
+        let cache = Cache::from_bytes(&postcard::to_stdvec(&old).unwrap()).unwrap();
+        assert_eq!(cache.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(cache.db.len(), 1);
+        assert_eq!(cache.generated, None);
+    }
+
+    #[test]
+    fn test_load_compressed_cache() {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let oui = Oui::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc").unwrap();
+        let cache = Cache {
+            last_modified: None,
+            etag: Some(String::from("\"abc\"")),
+            db: OuiDatabase::new(vec![oui]),
+            generated: None,
+            content_hash: Some(1234),
+        };
+        let plain = postcard::to_stdvec(&cache).unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&plain).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        for bytes in [&plain, &compressed] {
+            let loaded = Cache::from_bytes(bytes).unwrap();
+            assert_eq!(loaded.etag.as_deref(), Some("\"abc\""));
+            assert_eq!(loaded.db.entries(), cache.db.entries());
+            assert_eq!(loaded.content_hash, Some(1234));
+        }
+
+        assert!(Cache::from_bytes(&compressed[..compressed.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn test_refresh_unchanged() {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let gzip = |data: &str| {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(data.as_bytes()).unwrap();
+            encoder.finish().unwrap()
+        };
+        let manuf = gzip("00:50:F1\tMaxlinear\tMaxlinear, Inc\n");
+        let cache = Cache::from_manuf_gz(&manuf, None, Some(String::from("\"v1\""))).unwrap();
+        assert!(cache.content_hash.is_some());
+
+        // swap in a different database, which an identical download must leave alone
+        let marker = Oui::from_manuf("00:00:0C\tCisco\tCisco Systems, Inc").unwrap();
+        let cache = Cache { db: OuiDatabase::new(vec![marker]), ..cache };
+        let last_modified = Some(String::from("Sun, 15 Jun 2025 04:12:33 GMT"));
+        let cache = cache
+            .refresh(
+                &manuf,
+                DownloadFormat::ManufGz,
+                last_modified.clone(),
+                Some(String::from("\"v2\"")),
+            )
+            .unwrap();
+        assert_eq!(cache.db.len(), 1);
+        assert_eq!(cache.db.entries()[0].short_name(), "Cisco");
+        assert_eq!(cache.etag.as_deref(), Some("\"v2\""));
+        assert_eq!(cache.last_modified, last_modified);
+
+        // changed content is parsed again
+        let cache = cache
+            .refresh(&gzip("00:50:F2\tMicrosof\tMicrosoft\n"), DownloadFormat::ManufGz, None, None)
+            .unwrap();
+        assert_eq!(cache.db.entries()[0].short_name(), "Microsof");
+        assert_eq!(cache.etag, None);
+    }
+}
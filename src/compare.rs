@@ -0,0 +1,93 @@
+use std::io::Write;
+
+use oui_lookup::oui::{MacAddress, MacPrefix, Oui};
+
+use crate::output::{Printer, reported_vendor};
+
+/// Print the results for two addresses, then whether they're the same vendor and the longest
+/// prefix the addresses have in common.
+///
+/// Vendors are compared by their normalized names, so the same company's differently written
+/// entries count as the same vendor. Addresses with no vendor never match.
+pub fn run<'a>(
+    mut printer: Printer<impl Write>,
+    [a, b]: [MacAddress; 2],
+    lookup: impl Fn(MacAddress) -> Option<&'a Oui>,
+) -> std::io::Result<()> {
+    let found = [a, b].map(&lookup);
+    for (mac, found) in [a, b].into_iter().zip(found) {
+        printer.result(mac, found)?;
+    }
+    let mut out = printer.finish()?;
+
+    let same = match [reported_vendor(a, found[0]), reported_vendor(b, found[1])] {
+        [Some(a), Some(b)] => a.normalized_vendor() == b.normalized_vendor(),
+        _ => false,
+    };
+    writeln!(out, "same vendor: {}", if same { "yes" } else { "no" })?;
+    writeln!(out, "common prefix: {}", MacPrefix::common_prefix(a, b))?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::OutputArgs;
+    use oui_lookup::database::OuiDatabase;
+
+    #[test]
+    fn test_run() {
+        let db = OuiDatabase::from_manuf(
+            "\
+00:00:0C\tCisco\tCisco Systems, Inc
+00:03:93\tApple\tApple, Inc.
+00:0A:27\tApple\tApple Inc
+",
+        );
+        let compare_with = |args: &OutputArgs, a: &str, b: &str| {
+            let macs = [a, b].map(|mac| MacAddress::parse(mac).unwrap());
+            let mut out = Vec::new();
+            run(Printer::new(&mut out, args), macs, |mac| db.lookup(mac)).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let compare = |a: &str, b: &str| compare_with(&OutputArgs::default(), a, b);
+
+        // different prefixes, but the same vendor
+        assert_eq!(
+            compare("00:03:93:12:34:56", "00:0a:27:ab:cd:ef"),
+            "\
+00:03:93:12:34:56 - 00:03:93/24 - Apple, Inc.
+00:0a:27:ab:cd:ef - 00:0a:27/24 - Apple Inc
+same vendor: yes
+common prefix: 00:00/12
+"
+        );
+        assert_eq!(
+            compare("00:03:93:12:34:56", "00:00:0c:00:00:01"),
+            "\
+00:03:93:12:34:56 - 00:03:93/24 - Apple, Inc.
+00:00:0c:00:00:01 - 00:00:0c/24 - Cisco Systems, Inc
+same vendor: no
+common prefix: 00:00/14
+"
+        );
+        assert!(
+            compare("00:11:22:33:44:55", "00:11:22:33:44:56")
+                .ends_with("same vendor: no\ncommon prefix: 00:11:22:33:44:54/46\n")
+        );
+
+        // the results follow the output options, the comparison doesn't
+        let args = OutputArgs { short: true, raw: true, ..Default::default() };
+        assert_eq!(
+            compare_with(&args, "00:03:93:12:34:56", "00:0a:27:ab:cd:ef"),
+            "\
+00:03:93:12:34:56 - 00:03:93/24 - Apple
+    raw: mac 0x000393123456, prefix 0x1800000393000000
+00:0a:27:ab:cd:ef - 00:0a:27/24 - Apple
+    raw: mac 0x000a27abcdef, prefix 0x1800000a27000000
+same vendor: yes
+common prefix: 00:00/12
+"
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use serde::Deserialize;
+use serif::macros::*;
+
+/// Defaults for command line options, read from a TOML config file.
+///
+/// Each setting is the default value of the option with the same name, so flags given on the
+/// command line always take precedence.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Output template, like `--format`
+    pub format: Option<String>,
+    /// Database URL, like `--db-url`, only used with the default Wireshark source
+    pub db_url: Option<String>,
+    /// Cache file location, like `--cache-file`
+    pub cache_file: Option<PathBuf>,
+    /// HTTP timeout in seconds, like `--timeout`
+    pub timeout: Option<u64>,
+    /// Use an existing cache without checking for updates, like `--prefer-cache`
+    pub prefer_cache: Option<bool>,
+}
+
+impl Config {
+    /// Get the default config file path, `oui-lookup/config.toml` in the platform's config
+    /// directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("oui-lookup").join("config.toml"))
+    }
+
+    /// Load a config file, or the default config if it doesn't exist
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let data = match std::fs::read_to_string(path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                debug!("config file {} doesn't exist", path.display());
+                return Ok(Self::default());
+            }
+            Err(err) => return Err(err).context(format!("failed to read {}", path.display())),
+        };
+        toml::from_str(&data).with_context(|| format!("invalid config file {}", path.display()))
+    }
+
+    /// Set the default values of the options this config has settings for, in the command and
+    /// any subcommands which have the same options
+    pub fn apply(&self, mut cmd: clap::Command) -> clap::Command {
+        let defaults = [
+            ("format", self.format.clone()),
+            ("db_url", self.db_url.clone()),
+            ("cache_file", self.cache_file.as_ref().map(|p| p.to_string_lossy().into_owned())),
+            ("timeout", self.timeout.map(|t| t.to_string())),
+            ("no_revalidate", self.prefer_cache.map(|b| b.to_string())),
+        ];
+        for (id, value) in defaults {
+            if let Some(value) = value {
+                cmd = set_default(cmd, id, value);
+            }
+        }
+        cmd
+    }
+}
+
+fn set_default(mut cmd: clap::Command, id: &'static str, value: String) -> clap::Command {
+    if cmd.get_arguments().any(|arg| arg.get_id() == id) {
+        cmd = cmd.mut_arg(id, |arg| arg.default_value(value.clone()));
+    }
+    let names = cmd.get_subcommands().map(|sub| sub.get_name().to_string()).collect::<Vec<_>>();
+    for name in names {
+        cmd = cmd.mut_subcommand(name, |sub| set_default(sub, id, value.clone()));
+    }
+    cmd
+}
@@ -0,0 +1,116 @@
+use std::io::{Read, Write};
+use std::path::Path;
+
+use anyhow::Context as _;
+use csv::StringRecord;
+
+/// Name of the column added by `--augment`
+const VENDOR_COLUMN: &str = "vendor";
+
+/// The records of a CSV file, and which column holds their MAC addresses
+#[derive(Debug)]
+pub struct CsvInput {
+    headers: Option<StringRecord>,
+    records: Vec<StringRecord>,
+    column: usize,
+}
+
+impl CsvInput {
+    /// Parse CSV data, finding `column` by its header name or else as a 1-based column number
+    pub fn parse(data: impl Read, column: &str, has_headers: bool) -> anyhow::Result<Self> {
+        let mut reader =
+            csv::ReaderBuilder::new().has_headers(has_headers).flexible(true).from_reader(data);
+        let headers = has_headers.then(|| reader.headers().cloned()).transpose()?;
+        let index = headers
+            .as_ref()
+            .and_then(|headers| headers.iter().position(|name| name == column))
+            .or_else(|| column.parse::<usize>().ok().and_then(|n| n.checked_sub(1)))
+            .with_context(|| format!("no column '{column}' in the CSV input"))?;
+        let records = reader.records().collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { headers, records, column: index })
+    }
+
+    /// Read and parse a CSV file, or stdin if the path is "-"
+    pub fn read(path: &Path, column: &str, has_headers: bool) -> anyhow::Result<Self> {
+        let parsed = if path == Path::new("-") {
+            Self::parse(std::io::stdin().lock(), column, has_headers)
+        } else {
+            let fp = std::fs::File::open(path)
+                .with_context(|| format!("failed to open {}", path.display()))?;
+            Self::parse(fp, column, has_headers)
+        };
+        parsed.with_context(|| format!("invalid CSV input {}", path.display()))
+    }
+
+    /// Get the MAC address column of each record, an empty string if a record is too short
+    pub fn macs(&self) -> impl Iterator<Item = &str> {
+        self.records.iter().map(|record| record.get(self.column).unwrap_or_default())
+    }
+
+    /// Write the records back out as CSV with a vendor column added, which is empty for addresses
+    /// with no known vendor. `vendor` is called with the MAC address column of each record.
+    pub fn write_augmented<S: AsRef<str>>(
+        &self,
+        out: impl Write,
+        mut vendor: impl FnMut(&str) -> Option<S>,
+    ) -> anyhow::Result<()> {
+        let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(out);
+        if let Some(headers) = &self.headers {
+            writer.write_record(headers.iter().chain([VENDOR_COLUMN]))?;
+        }
+        for (record, mac) in self.records.iter().zip(self.macs()) {
+            let vendor = vendor(mac);
+            let vendor = vendor.as_ref().map_or("", AsRef::as_ref);
+            writer.write_record(record.iter().chain([vendor]))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INVENTORY: &str = "\
+host,mac,room
+printer,00:50:f1:12:34:56,101
+\"desk, left\",00-11-22-33-44-55,102
+laptop,nope,103
+";
+
+    fn augment(input: &CsvInput) -> String {
+        let mut out = Vec::new();
+        input
+            .write_augmented(&mut out, |mac| {
+                mac.starts_with("00:50:f1").then_some("Maxlinear, Inc")
+            })
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_augment() {
+        let input = CsvInput::parse(INVENTORY.as_bytes(), "mac", true).unwrap();
+        assert!(input.macs().eq(["00:50:f1:12:34:56", "00-11-22-33-44-55", "nope"]));
+        assert_eq!(
+            augment(&input),
+            "\
+host,mac,room,vendor
+printer,00:50:f1:12:34:56,101,\"Maxlinear, Inc\"
+\"desk, left\",00-11-22-33-44-55,102,
+laptop,nope,103,
+"
+        );
+
+        // a column number works with or without headers
+        let input = CsvInput::parse(INVENTORY.as_bytes(), "2", true).unwrap();
+        assert_eq!(input.macs().count(), 3);
+        let input = CsvInput::parse(INVENTORY.as_bytes(), "2", false).unwrap();
+        assert!(input.macs().eq(["mac", "00:50:f1:12:34:56", "00-11-22-33-44-55", "nope"]));
+        assert!(augment(&input).starts_with("host,mac,room,\nprinter,"));
+
+        assert!(CsvInput::parse(INVENTORY.as_bytes(), "address", true).is_err());
+        assert!(CsvInput::parse(INVENTORY.as_bytes(), "0", true).is_err());
+    }
+}
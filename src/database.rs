@@ -0,0 +1,813 @@
+use std::collections::HashSet;
+use std::io::{self, BufRead};
+use std::sync::Arc;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::oui::{self, MacAddress, MacPrefix, Oui};
+use crate::trie::TrieDatabase;
+
+/// An owned copy of a database match, independent of the database's lifetime.
+///
+/// This is meant for callers like FFI bindings which can't hold a borrow of the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OuiMatch {
+    pub mac_prefix: MacPrefix,
+    pub short_name: String,
+    pub long_name: String,
+}
+
+impl From<&Oui> for OuiMatch {
+    fn from(oui: &Oui) -> Self {
+        Self {
+            mac_prefix: oui.mac_prefix,
+            short_name: oui.short_name().to_string(),
+            long_name: oui.long_name().to_string(),
+        }
+    }
+}
+
+/// A collection of OUI entries sorted by prefix, for looking up MAC addresses.
+///
+/// Entries with the same prefix are all kept, adjacent to each other.
+///
+/// Vendor names are interned, entries with the same name share one allocation. Large vendors own
+/// dozens or hundreds of prefixes, so this saves a good chunk of the memory a full database takes.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct OuiDatabase {
+    entries: Vec<Oui>,
+}
+
+impl<'de> Deserialize<'de> for OuiDatabase {
+    /// Deserialize the entries as is, only interning their names.
+    ///
+    /// Entries are expected to be sorted already, see [`check_invariants`](Self::check_invariants).
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut entries = Vec::<Oui>::deserialize(deserializer)?;
+        intern_names(&mut entries);
+        Ok(Self { entries })
+    }
+}
+
+/// Make entries with equal names share the same allocation
+fn intern_names(entries: &mut [Oui]) {
+    let mut names = HashSet::<Arc<str>>::new();
+    let mut intern = |name: &mut Arc<str>| match names.get(name) {
+        Some(interned) => *name = interned.clone(),
+        None => {
+            names.insert(name.clone());
+        }
+    };
+    for oui in entries {
+        intern(&mut oui.short_name);
+        intern(&mut oui.long_name);
+    }
+}
+
+/// Find the entry with the longest prefix matching `mac` in entries sorted by prefix.
+///
+/// Of conflicting entries with the same prefix, the first one wins.
+pub fn best_match(entries: &[Oui], mac: MacAddress) -> Option<&Oui> {
+    best_match_index(entries, mac).map(|i| &entries[i])
+}
+
+/// Find the index of the longest entry matching `mac` using binary search.
+///
+/// Nested entries mean the first match found isn't necessarily the longest, so this checks all of
+/// them.
+fn best_match_index(entries: &[Oui], mac: MacAddress) -> Option<usize> {
+    // iterating backwards, so `>=` prefers the first of any conflicting entries
+    matching_indices(entries, mac).reduce(|best, i| {
+        if entries[i].prefix_len() >= entries[best].prefix_len() { i } else { best }
+    })
+}
+
+/// Iterate backward over the indices of the entries matching `mac`.
+///
+/// Starting from the last entry with a base address at or below `mac`, this walks backward over
+/// the entries which could still cover it. An entry can only match if its base is `mac` masked to
+/// its own length, so there are at most 49 candidate bases, one for each prefix length from 48
+/// down to 0. When an entry doesn't match, everything back to the next lower candidate base is
+/// skipped with another binary search.
+fn matching_indices(entries: &[Oui], mac: MacAddress) -> impl Iterator<Item = usize> {
+    let mut end = entries.partition_point(|oui| oui.mac() <= mac);
+    std::iter::from_fn(move || {
+        while let Some(i) = end.checked_sub(1) {
+            let oui = &entries[i];
+            if oui.mac_prefix.matches(mac) {
+                end = i;
+                return Some(i);
+            }
+            // the highest candidate base at or below this entry's, /0 is always one
+            let base = oui.mac();
+            let next = (0..=48)
+                .rev()
+                .map(|len| MacPrefix::from_parts(mac, len).mac())
+                .find(|&candidate| candidate <= base)
+                .unwrap();
+            end = if next == base {
+                i
+            } else {
+                entries[..i].partition_point(|oui| oui.mac() <= next)
+            };
+        }
+        None
+    })
+}
+
+/// Databases with fewer entries than this are searched with a simple linear scan
+const LINEAR_SCAN_MAX_LEN: usize = 32;
+
+impl OuiDatabase {
+    /// Build a database from entries in any order.
+    ///
+    /// Conflicting entries with the same prefix are ordered by long name, short name, and then
+    /// mailing address, so which one wins a lookup doesn't depend on the input order. Entries
+    /// which tie on all of those are indistinguishable, so an unstable sort is used, which is
+    /// quicker for a full database of tens of thousands of entries.
+    pub fn new(mut entries: Vec<Oui>) -> Self {
+        entries.sort_unstable_by(|a, b| {
+            a.mac_prefix
+                .cmp(&b.mac_prefix)
+                .then_with(|| a.long_name().cmp(b.long_name()))
+                .then_with(|| a.short_name().cmp(b.short_name()))
+                .then_with(|| a.address.cmp(&b.address))
+        });
+        intern_names(&mut entries);
+        Self { entries }
+    }
+
+    /// Merge databases from several sources, given from highest to lowest precedence.
+    ///
+    /// When sources have conflicting entries for the same prefix, those from the higher
+    /// precedence source come first and win lookups. Conflicts within a source keep their order
+    /// from [`new`](Self::new). Tag each source with [`set_source`](Self::set_source) first to
+    /// tell afterwards which one an entry came from.
+    pub fn merge(sources: impl IntoIterator<Item = OuiDatabase>) -> Self {
+        let mut entries = sources
+            .into_iter()
+            .enumerate()
+            .flat_map(|(source, db)| {
+                db.entries.into_iter().enumerate().map(move |(i, oui)| ((source, i), oui))
+            })
+            .collect::<Vec<_>>();
+        // each source's own order is kept by its index, no two entries have the same key
+        entries.sort_unstable_by_key(|(position, oui)| (oui.mac_prefix, *position));
+        let mut entries = entries.into_iter().map(|(_, oui)| oui).collect::<Vec<_>>();
+        intern_names(&mut entries);
+        Self { entries }
+    }
+
+    /// Tag every entry as coming from `source`, replacing any previous tag
+    pub fn set_source(&mut self, source: &str) {
+        let source = Arc::<str>::from(source);
+        for oui in &mut self.entries {
+            oui.source = Some(source.clone());
+        }
+    }
+
+    /// Build a database from the contents of a manuf file, skipping any lines which don't parse
+    pub fn from_manuf(data: &str) -> Self {
+        Self::new(data.lines().filter_map(Oui::from_manuf).collect())
+    }
+
+    /// Build a database by reading a manuf file, skipping any lines which don't parse
+    pub fn from_manuf_reader(reader: impl BufRead) -> io::Result<Self> {
+        Ok(Self::from_manuf_reader_verbose(reader)?.0)
+    }
+
+    /// Build a database by reading a manuf file, also returning the 1-based line number and
+    /// content of each line which was skipped because it didn't parse.
+    ///
+    /// Blank lines and comments aren't reported.
+    pub fn from_manuf_reader_verbose(
+        reader: impl BufRead,
+    ) -> io::Result<(Self, Vec<(usize, String)>)> {
+        let mut entries = Vec::new();
+        let mut skipped = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            match Oui::from_manuf(&line) {
+                Some(oui) => entries.push(oui),
+                None => skipped.push((i + 1, line)),
+            }
+        }
+        Ok((Self::new(entries), skipped))
+    }
+
+    /// Build a database from the contents of an IEEE registry CSV file, skipping the header and
+    /// any rows which don't parse
+    pub fn from_ieee_csv(data: &str) -> Self {
+        let mut reader = oui::ieee_csv_reader(data.as_bytes());
+        let records = reader.records().map_while(Result::ok);
+        Self::new(records.filter_map(|record| Oui::from_ieee_record(&record)).collect())
+    }
+
+    /// Get all entries, sorted by prefix
+    #[inline]
+    pub fn entries(&self) -> &[Oui] {
+        &self.entries
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Verify the database's internal invariants, for tests and fuzzing.
+    ///
+    /// Entries must be sorted by prefix, and every prefix must be at most 48 bits with no address
+    /// bits set beyond its length. A database built with [`new`](Self::new) always passes, but
+    /// one deserialized from a corrupt cache might not.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        for (i, oui) in self.entries.iter().enumerate() {
+            let prefix = oui.mac_prefix;
+            if prefix.prefix_len() > 48 {
+                return Err(format!("entry {i} has invalid prefix length {}", prefix.prefix_len()));
+            }
+            if MacPrefix::from_parts(prefix.mac(), prefix.prefix_len()) != prefix {
+                return Err(format!("entry {i} prefix {prefix} isn't masked to its length"));
+            }
+            if let Some(next) = self.entries.get(i + 1)
+                && next.mac_prefix < prefix
+            {
+                return Err(format!("entry {i} {prefix} is sorted before {}", next.mac_prefix));
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the database entry matching a MAC address
+    pub fn lookup(&self, mac: MacAddress) -> Option<&Oui> {
+        self.lookup_all(mac).first()
+    }
+
+    /// Replace the vendor names of entries, e.g. to correct outdated names.
+    ///
+    /// `rename` returns the new long and short names for an entry, with `None` leaving that name
+    /// alone. Entries keep their order, so renaming doesn't change which of several conflicting
+    /// entries wins a lookup.
+    pub fn rename_vendors<'a>(
+        &mut self,
+        mut rename: impl FnMut(&Oui) -> (Option<&'a str>, Option<&'a str>),
+    ) {
+        for oui in &mut self.entries {
+            let (long, short) = rename(oui);
+            if let Some(long) = long {
+                oui.long_name = Arc::from(long);
+            }
+            if let Some(short) = short {
+                oui.short_name = Arc::from(short);
+            }
+        }
+        intern_names(&mut self.entries);
+    }
+
+    /// Check whether any database entry covers a MAC address.
+    ///
+    /// This is cheaper than [`lookup`](Self::lookup) when the vendor doesn't matter, since it stops
+    /// at the first matching entry rather than searching for the longest.
+    pub fn is_known(&self, mac: MacAddress) -> bool {
+        matching_indices(&self.entries, mac).next().is_some()
+    }
+
+    /// Build a trie of the entries, an alternative backend for looking up addresses
+    pub fn build_trie(&self) -> TrieDatabase<'_> {
+        TrieDatabase::new(self)
+    }
+
+    /// Find the database entry matching a MAC address, returning an owned copy of it
+    pub fn lookup_owned(&self, mac: MacAddress) -> Option<OuiMatch> {
+        self.lookup(mac).map(OuiMatch::from)
+    }
+
+    /// Find every database entry whose prefix matches a MAC address, not just the longest.
+    ///
+    /// The order is stable between runs: longest prefix first, then by base address, then by long
+    /// name and short name.
+    pub fn all_matches(&self, mac: MacAddress) -> Vec<&Oui> {
+        let mut matches =
+            matching_indices(&self.entries, mac).map(|i| &self.entries[i]).collect::<Vec<_>>();
+        matches.sort_by(|a, b| {
+            b.prefix_len()
+                .cmp(&a.prefix_len())
+                .then_with(|| a.mac().cmp(&b.mac()))
+                .then_with(|| a.long_name().cmp(b.long_name()))
+                .then_with(|| a.short_name().cmp(b.short_name()))
+        });
+        matches
+    }
+
+    /// Find all the database entries with the longest prefix matching a MAC address.
+    ///
+    /// This is normally zero or one entries, but could be more if the data has conflicting names
+    /// for the same prefix. They're in database order, so the first is the one
+    /// [`lookup`](Self::lookup) returns: from the highest precedence source when
+    /// [merged](Self::merge), and otherwise the first by long name and then short name.
+    pub fn lookup_all(&self, mac: MacAddress) -> &[Oui] {
+        let best = if self.entries.len() < LINEAR_SCAN_MAX_LEN {
+            // the first of any conflicting entries wins, so only replace on a strictly longer match
+            let mut best: Option<usize> = None;
+            for (i, oui) in self.entries.iter().enumerate() {
+                if oui.mac_prefix.matches(mac)
+                    && best.is_none_or(|b| oui.prefix_len() > self.entries[b].prefix_len())
+                {
+                    best = Some(i);
+                }
+            }
+            best
+        } else {
+            best_match_index(&self.entries, mac)
+        };
+        let Some(index) = best else {
+            return &[];
+        };
+
+        let prefix = self.entries[index].mac_prefix;
+        let same = |oui: &&Oui| oui.mac_prefix == prefix;
+        let start = index - self.entries[..index].iter().rev().take_while(same).count();
+        let end = index + 1 + self.entries[index + 1..].iter().take_while(same).count();
+        &self.entries[start..end]
+    }
+
+    /// Find the entries immediately before and after a MAC address in sorted order.
+    ///
+    /// This is mostly useful when there's no match, to show the closest known prefixes.
+    pub fn neighbors(&self, mac: MacAddress) -> (Option<&Oui>, Option<&Oui>) {
+        let index = self.entries.partition_point(|oui| oui.mac() <= mac);
+        let before = index.checked_sub(1).map(|i| &self.entries[i]);
+        (before, self.entries.get(index))
+    }
+
+    /// Find the next prefix after a MAC address which belongs to a different vendor than the
+    /// address does, and the number of addresses from `mac` up to its start.
+    ///
+    /// Returns `None` if `mac` has no vendor, or no other vendor's prefixes follow it.
+    pub fn next_vendor_boundary(&self, mac: MacAddress) -> Option<(u64, &Oui)> {
+        let vendor = self.lookup(mac)?;
+        let index = self.entries.partition_point(|oui| oui.mac() <= mac);
+        let next = self.entries[index..].iter().find(|oui| oui.long_name != vendor.long_name)?;
+        Some((next.mac().to_u64() - mac.to_u64(), next))
+    }
+
+    /// Find all database entries contained within a prefix
+    pub fn entries_under(&self, prefix: MacPrefix) -> impl Iterator<Item = &Oui> {
+        let start = self.entries.partition_point(|oui| oui.mac() < prefix.mac());
+        self.entries[start..]
+            .iter()
+            .take_while(move |oui| prefix.matches(oui.mac()))
+            .filter(move |oui| oui.prefix_len() >= prefix.prefix_len())
+    }
+
+    /// Find the address ranges within a prefix which aren't covered by any database entry.
+    ///
+    /// Ranges are inclusive `(first, last)` pairs in ascending order. If an entry contains the
+    /// whole prefix there are no gaps.
+    pub fn gaps(&self, parent: MacPrefix) -> Vec<(MacAddress, MacAddress)> {
+        if self.lookup(parent.mac()).is_some_and(|oui| oui.prefix_len() <= parent.prefix_len()) {
+            return Vec::new();
+        }
+
+        let mut gaps = Vec::new();
+        let mut next = parent.mac().to_u64();
+        for oui in self.entries_under(parent) {
+            let start = oui.mac().to_u64();
+            if start > next {
+                gaps.push((MacAddress::from_u64(next), MacAddress::from_u64(start - 1)));
+            }
+            next = next.max(oui.mac_prefix.last().to_u64() + 1);
+        }
+        let end = parent.last().to_u64();
+        if next <= end {
+            gaps.push((MacAddress::from_u64(next), MacAddress::from_u64(end)));
+        }
+        gaps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_lookup_conflicts() {
+        let db = OuiDatabase::from_manuf(
+            "\
+00:55:DA\tIeeeRegi\tIEEE Registration Authority
+00:50:F1\tMaxlinear\tMaxlinear, Inc
+00:50:F2\tMicrosoft\tMicrosoft
+00:50:F1\tOther\tSomebody Else
+",
+        );
+        let names = |mac: &str| {
+            let mac = MacAddress::parse(mac).unwrap();
+            db.lookup_all(mac).iter().map(|oui| oui.short_name()).collect::<Vec<_>>()
+        };
+        assert_eq!(names("00:50:f1:12:34:56"), ["Maxlinear", "Other"]);
+        assert_eq!(names("00:50:f2:12:34:56"), ["Microsoft"]);
+        assert_eq!(names("00:55:da:12:34:56"), ["IeeeRegi"]);
+        assert!(names("00:11:22:33:44:55").is_empty());
+
+        let mac = MacAddress::parse("00:50:f1:12:34:56").unwrap();
+        assert_eq!(db.lookup(mac).unwrap().short_name(), "Maxlinear");
+    }
+
+    #[test]
+    fn test_from_manuf_reader_verbose() {
+        let data = "# comment\n\
+                    00:00:0C\tCisco\tCisco Systems, Inc\n\
+                    \n\
+                    zz:00:0C\tBad\tBad Prefix\n\
+                    00:50:F1\tMaxlinear\tMaxlinear, Inc\n\
+                    00:11:22\tNoLongName\n";
+        let (db, skipped) = OuiDatabase::from_manuf_reader_verbose(data.as_bytes()).unwrap();
+        assert_eq!(db.len(), 2);
+        assert_eq!(
+            skipped,
+            [
+                (4, String::from("zz:00:0C\tBad\tBad Prefix")),
+                (6, String::from("00:11:22\tNoLongName"))
+            ]
+        );
+        assert_eq!(
+            OuiDatabase::from_manuf_reader(data.as_bytes()).unwrap().entries(),
+            db.entries()
+        );
+    }
+
+    #[test]
+    fn test_small_overlapping() {
+        let db = OuiDatabase::from_manuf(
+            "\
+00:00:00:00:00:00/8\tZero\tWhole /8
+00:55:DA\tIeeeRegi\tIEEE Registration Authority
+00:55:DA:50:00:00/28\tNested28\tNested /28
+00:55:DA:5F:A0:00/36\tNested36\tNested /36
+00:50:F1\tMaxlinear\tMaxlinear, Inc
+",
+        );
+        assert!(db.len() < LINEAR_SCAN_MAX_LEN);
+        for (mac, expected) in [
+            ("00:55:da:5f:a1:23", Some("Nested36")),
+            ("00:55:da:5f:b1:23", Some("Nested28")),
+            ("00:55:da:50:00:00", Some("Nested28")),
+            ("00:55:da:60:00:00", Some("IeeeRegi")),
+            ("00:50:f1:12:34:56", Some("Maxlinear")),
+            ("00:11:22:33:44:55", Some("Zero")),
+            ("01:55:da:5f:a1:23", None),
+        ] {
+            let mac = MacAddress::parse(mac).unwrap();
+            let name = db.lookup(mac).map(|oui| oui.short_name());
+            assert_eq!(name, expected, "{mac}");
+            // the binary search used for larger databases agrees
+            let searched = best_match(db.entries(), mac).map(|oui| oui.short_name());
+            assert_eq!(searched, expected, "{mac}");
+        }
+    }
+
+    #[test]
+    fn test_conflict_tie_break() {
+        let public = "00:50:F1\tMaxlinear\tMaxlinear, Inc\n";
+        let private = "00:50:F1\tLab\tLab Equipment\n";
+        let mac = MacAddress::parse("00:50:f1:12:34:56").unwrap();
+        let winner = |db: OuiDatabase| db.lookup(mac).unwrap().short_name().to_string();
+
+        // the higher precedence source wins, whichever name sorts first
+        let merged = |a, b| OuiDatabase::merge([a, b].map(OuiDatabase::from_manuf));
+        assert_eq!(winner(merged(private, public)), "Lab");
+        assert_eq!(winner(merged(public, private)), "Maxlinear");
+
+        // within one source, the name decides regardless of file order
+        assert_eq!(winner(OuiDatabase::from_manuf(&format!("{public}{private}"))), "Lab");
+        assert_eq!(winner(OuiDatabase::from_manuf(&format!("{private}{public}"))), "Lab");
+    }
+
+    #[test]
+    fn test_conflict_order_deterministic() {
+        let lines = [
+            "00:50:F1\tLab\tLab Equipment",
+            "00:50:F1\tMaxlinear\tMaxlinear, Inc",
+            "00:50:F1\tMxl\tMaxlinear, Inc",
+            "00:50:F0\tBefore\tBefore",
+            "00:50:F1:80/25\tHalf\tHalf",
+        ];
+        let expected = [
+            "00:50:f0/24 Before",
+            "00:50:f1/24 Lab",
+            "00:50:f1/24 Maxlinear",
+            "00:50:f1/24 Mxl",
+            "00:50:f1:80/25 Half",
+        ];
+        let order = |db: &OuiDatabase| {
+            db.entries()
+                .iter()
+                .map(|oui| format!("{} {}", oui.mac_prefix, oui.short_name()))
+                .collect::<Vec<_>>()
+        };
+
+        // every rotation and its reverse, as whole files and merged from a single source
+        for rotation in 0..lines.len() {
+            let mut rotated = lines;
+            rotated.rotate_left(rotation);
+            let mut reversed = rotated;
+            reversed.reverse();
+            for lines in [rotated, reversed] {
+                let data = lines.join("\n");
+                assert_eq!(order(&OuiDatabase::from_manuf(&data)), expected, "{data}");
+                let merged = OuiDatabase::merge([OuiDatabase::from_manuf(&data)]);
+                assert_eq!(order(&merged), expected, "{data}");
+            }
+        }
+
+        // IEEE CSV entries which only differ by address are ordered too
+        let csv = [
+            "MA-L,0050F1,\"Maxlinear, Inc\",Carlsbad CA US",
+            "MA-L,0050F1,\"Maxlinear, Inc\",Austin TX US",
+        ];
+        for csv in [csv, [csv[1], csv[0]]] {
+            let db = OuiDatabase::new(csv.iter().filter_map(|s| Oui::from_ieee_csv(s)).collect());
+            let addresses =
+                db.entries().iter().map(|oui| oui.address.as_deref()).collect::<Vec<_>>();
+            assert_eq!(addresses, [Some("Austin TX US"), Some("Carlsbad CA US")]);
+        }
+    }
+
+    #[test]
+    fn test_merge_sources() {
+        let mut public = OuiDatabase::from_manuf(
+            "00:00:0C\tCisco\tCisco Systems, Inc\n00:50:F1\tMaxlinear\tMaxlinear, Inc\n",
+        );
+        public.set_source("public");
+        let mut overlay = OuiDatabase::from_manuf("00:50:F1\tLab\tLab Equipment\n");
+        overlay.set_source("override");
+        let db = OuiDatabase::merge([overlay, public]);
+
+        let source = |mac: &str| {
+            let oui = db.lookup(MacAddress::parse(mac).unwrap()).unwrap();
+            (oui.short_name().to_string(), oui.source.as_deref().map(String::from))
+        };
+        assert_eq!(
+            source("00:50:f1:12:34:56"),
+            (String::from("Lab"), Some(String::from("override")))
+        );
+        assert_eq!(
+            source("00:00:0c:12:34:56"),
+            (String::from("Cisco"), Some(String::from("public")))
+        );
+    }
+
+    #[test]
+    fn test_interned_names() {
+        let data = "\
+00:00:0C\tCisco\tCisco Systems, Inc
+00:01:42\tCisco\tCisco Systems, Inc
+00:50:F1\tMaxlinear\tMaxlinear, Inc
+";
+        let db = OuiDatabase::from_manuf(data);
+        let [a, b, c] = db.entries() else { panic!() };
+        assert!(Arc::ptr_eq(&a.long_name, &b.long_name));
+        assert!(Arc::ptr_eq(&a.short_name, &b.short_name));
+        assert!(!Arc::ptr_eq(&a.long_name, &c.long_name));
+
+        // interning survives a cache round trip and doesn't change lookups
+        let bytes = postcard::to_stdvec(&db).unwrap();
+        let loaded = postcard::from_bytes::<OuiDatabase>(&bytes).unwrap();
+        let [a, b, _] = loaded.entries() else { panic!() };
+        assert!(Arc::ptr_eq(&a.long_name, &b.long_name));
+        for mac in
+            ["00:00:0c:12:34:56", "00:01:42:12:34:56", "00:50:f1:12:34:56", "00:11:22:33:44:55"]
+        {
+            let mac = MacAddress::parse(mac).unwrap();
+            assert_eq!(loaded.lookup_owned(mac), db.lookup_owned(mac));
+        }
+        assert_eq!(
+            db.lookup(MacAddress::parse("00:01:42:12:34:56").unwrap()).unwrap().long_name(),
+            "Cisco Systems, Inc"
+        );
+    }
+
+    #[test]
+    fn test_next_vendor_boundary() {
+        let db = OuiDatabase::from_manuf(
+            "\
+00:50:F0\tCisco\tCisco Systems, Inc
+00:50:F1\tCisco\tCisco Systems, Inc
+00:50:F2\tMicrosoft\tMicrosoft
+",
+        );
+        let boundary = |mac| {
+            db.next_vendor_boundary(MacAddress::parse(mac).unwrap())
+                .map(|(n, oui)| (n, oui.mac_prefix.to_string()))
+        };
+        // the adjacent prefix from the same vendor doesn't count
+        assert_eq!(boundary("00:50:f0:00:00:00"), Some((2 << 24, String::from("00:50:f2/24"))));
+        assert_eq!(boundary("00:50:f1:ff:ff:ff"), Some((1, String::from("00:50:f2/24"))));
+        // the last entry, and an address with no vendor
+        assert_eq!(boundary("00:50:f2:00:00:00"), None);
+        assert_eq!(boundary("00:50:ef:00:00:00"), None);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let db = OuiDatabase::from_manuf(
+            "\
+00:50:F1\tMaxlinear\tMaxlinear, Inc
+00:50:F4\tSigmatek\tSIGMATEK GMBH & CO. KG
+00:55:DA\tIeeeRegi\tIEEE Registration Authority
+",
+        );
+        let neighbors = |mac: &str| {
+            let (before, after) = db.neighbors(MacAddress::parse(mac).unwrap());
+            (before.map(|oui| oui.short_name()), after.map(|oui| oui.short_name()))
+        };
+        assert_eq!(neighbors("00:50:f2:00:00:01"), (Some("Maxlinear"), Some("Sigmatek")));
+        assert_eq!(neighbors("00:50:f4:00:00:00"), (Some("Sigmatek"), Some("IeeeRegi")));
+        assert_eq!(neighbors("00:00:00:00:00:01"), (None, Some("Maxlinear")));
+        assert_eq!(neighbors("00:66:00:00:00:01"), (Some("IeeeRegi"), None));
+    }
+
+    #[test]
+    fn test_lookup_owned() {
+        let mac = MacAddress::parse("00:50:f1:12:34:56").unwrap();
+        let found = {
+            let db = OuiDatabase::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc\n");
+            db.lookup_owned(mac)
+        };
+        // the database has been dropped, the match lives on
+        let found = found.unwrap();
+        assert_eq!(found.mac_prefix.to_string(), "00:50:f1/24");
+        assert_eq!(found.short_name, "Maxlinear");
+        assert_eq!(found.long_name, "Maxlinear, Inc");
+        assert!(found.mac_prefix.matches(mac));
+
+        let db = OuiDatabase::default();
+        assert_eq!(db.lookup_owned(mac), None);
+    }
+
+    #[test]
+    fn test_gaps() {
+        let db = OuiDatabase::from_manuf(
+            "\
+00:55:D9\tBefore\tBefore
+00:55:DA:50/28\tNanoleaf\tNanoleaf
+00:55:DB\tAfter\tAfter
+",
+        );
+        let p = |s| MacPrefix::parse(s).unwrap();
+        let m = |s| MacAddress::parse(s).unwrap();
+        assert_eq!(
+            db.gaps(p("00:55:da")),
+            [
+                (m("00:55:da:00:00:00"), m("00:55:da:4f:ff:ff")),
+                (m("00:55:da:60:00:00"), m("00:55:da:ff:ff:ff")),
+            ]
+        );
+        assert_eq!(db.gaps(p("00:55:da:50/28")), []);
+        assert_eq!(db.gaps(p("00:55:d9:12/32")), []);
+        assert_eq!(db.gaps(p("00:55:dc")), [(m("00:55:dc:00:00:00"), m("00:55:dc:ff:ff:ff"))]);
+    }
+
+    proptest! {
+        #[test]
+        fn test_invariants(prefixes in prop::collection::vec((any::<[u8; 6]>(), 1..=48u8), 0..64)) {
+            let entries = prefixes
+                .into_iter()
+                .map(|(mac, len)| Oui::new(MacPrefix::from_parts(mac.into(), len), "", ""))
+                .collect::<Vec<_>>();
+            let db = OuiDatabase::new(entries);
+            prop_assert_eq!(db.check_invariants(), Ok(()));
+        }
+
+        #[test]
+        fn test_best_match_linear(
+            prefixes in prop::collection::vec((any::<[u8; 2]>(), 0..=24u8), 0..64),
+            mac in any::<[u8; 2]>(),
+        ) {
+            // squeeze everything into a small space so the entries nest and the address hits them
+            let mac6 = |[a, b]: [u8; 2]| MacAddress::from([0, 0x50, 0xf1, a, b, 0]);
+            let entries = prefixes
+                .into_iter()
+                .map(|(mac, len)| Oui::new(MacPrefix::from_parts(mac6(mac), 24 + len), "", ""))
+                .collect::<Vec<_>>();
+            let db = OuiDatabase::new(entries);
+            let mac = mac6(mac);
+            let linear = db
+                .entries()
+                .iter()
+                .filter(|oui| oui.mac_prefix.matches(mac))
+                .max_by_key(|oui| oui.prefix_len())
+                .map(|oui| oui.mac_prefix);
+            prop_assert_eq!(best_match(db.entries(), mac).map(|oui| oui.mac_prefix), linear);
+        }
+    }
+
+    #[test]
+    fn test_best_match_overlapping() {
+        // enough unrelated entries that lookups don't use a linear scan
+        let mut manuf =
+            (0..64).map(|i| format!("00:10:{i:02X}\tFiller\tFiller\n")).collect::<String>();
+        manuf.push_str(
+            "\
+00:55:DA\tIeeeRegi\tIEEE Registration Authority
+00:55:DA:50:00:00/28\tNested28\tNested /28
+00:55:DA:5F:A0:00/36\tNested36\tNested /36
+00:55:DA:5F:B0:00/36\tNext36\tNext /36
+00:55:DB\tAfter\tAfter
+",
+        );
+        let db = OuiDatabase::from_manuf(&manuf);
+        assert!(db.len() >= LINEAR_SCAN_MAX_LEN);
+        for (mac, expected) in [
+            ("00:55:da:5f:af:ff", Some("Nested36")),
+            ("00:55:da:5f:bf:ff", Some("Next36")),
+            ("00:55:da:5f:c0:00", Some("Nested28")),
+            ("00:55:da:5f:ff:ff", Some("Nested28")),
+            ("00:55:da:50:00:00", Some("Nested28")),
+            ("00:55:da:4f:ff:ff", Some("IeeeRegi")),
+            ("00:55:da:60:00:00", Some("IeeeRegi")),
+            ("00:55:db:00:00:00", Some("After")),
+            ("00:55:d9:ff:ff:ff", None),
+        ] {
+            let mac = MacAddress::parse(mac).unwrap();
+            let name = best_match(db.entries(), mac).map(|oui| oui.short_name());
+            assert_eq!(name, expected, "{mac}");
+            assert_eq!(db.lookup(mac).map(|oui| oui.short_name()), expected, "{mac}");
+        }
+    }
+
+    #[test]
+    fn test_all_matches() {
+        let sources = [
+            "00:55:DA\tIeeeRegi\tIEEE Registration Authority\n00:55:DA:5F:A0:00/36\tZeta\tZeta\n",
+            "\
+00:00:00:00:00:00/8\tZero\tWhole /8
+00:55:DA:50:00:00/28\tNested28\tNested /28
+00:55:DA:5F:A0:00/36\tAlpha\tAlpha
+00:55:DB\tOther\tOther
+",
+        ];
+        let mac = MacAddress::parse("00:55:da:5f:a1:23").unwrap();
+        let names = |db: &OuiDatabase| {
+            db.all_matches(mac).iter().map(|oui| oui.short_name().to_string()).collect::<Vec<_>>()
+        };
+        let expected = ["Alpha", "Zeta", "Nested28", "IeeeRegi", "Zero"];
+        // the same whichever source takes precedence
+        let db = OuiDatabase::merge(sources.map(OuiDatabase::from_manuf));
+        assert_eq!(names(&db), expected);
+        let [a, b] = sources;
+        let db = OuiDatabase::merge([b, a].map(OuiDatabase::from_manuf));
+        assert_eq!(names(&db), expected);
+
+        assert!(db.all_matches(MacAddress::BROADCAST).is_empty());
+    }
+
+    #[test]
+    fn test_is_known() {
+        let db = OuiDatabase::from_manuf(
+            "\
+00:00:00:00:00:00/8\tZero\tWhole /8
+00:55:DA\tIeeeRegi\tIEEE Registration Authority
+00:55:DA:50:00:00/28\tNested28\tNested /28
+00:50:F1\tMaxlinear\tMaxlinear, Inc
+02:00:00:00:00:00/8\tTwo\tTwo /8
+02:55:DA:5F:A0:00/36\tNested36\tNested /36
+",
+        );
+        for mac in [
+            "00:55:da:50:12:34",
+            "00:55:da:60:00:00",
+            "00:11:22:33:44:55",
+            "01:55:da:50:12:34",
+            "02:55:da:5f:a1:23",
+            "02:55:da:5f:b1:23",
+            "03:00:00:00:00:00",
+            "ff:ff:ff:ff:ff:ff",
+        ] {
+            let mac = MacAddress::parse(mac).unwrap();
+            assert_eq!(db.is_known(mac), db.lookup(mac).is_some(), "{mac}");
+        }
+        assert!(!OuiDatabase::default().is_known(MacAddress::BROADCAST));
+    }
+
+    #[test]
+    fn test_invariants_unsorted() {
+        let unsorted = "00:50:F4\tSigmatek\tSigmatek\n00:50:F1\tMaxlinear\tMaxlinear, Inc\n";
+        let entries = unsorted.lines().filter_map(Oui::from_manuf).collect::<Vec<_>>();
+        // deserializing doesn't sort
+        let bytes = postcard::to_stdvec(&entries).unwrap();
+        let db = postcard::from_bytes::<OuiDatabase>(&bytes).unwrap();
+        assert!(db.check_invariants().is_err());
+        assert_eq!(OuiDatabase::new(entries).check_invariants(), Ok(()));
+    }
+}
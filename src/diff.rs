@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+use oui_lookup::database::OuiDatabase;
+use oui_lookup::oui::{MacPrefix, Oui};
+
+use crate::db;
+
+/// Changes between two databases, keyed on the prefix of each entry
+#[derive(Debug, Default)]
+struct Diff<'a> {
+    added: Vec<&'a Oui>,
+    removed: Vec<&'a Oui>,
+    /// Pairs of the old and new entry for prefixes whose vendor name changed
+    renamed: Vec<(&'a Oui, &'a Oui)>,
+}
+
+impl<'a> Diff<'a> {
+    /// Compare two databases. Only the first entry for a prefix is considered if there are
+    /// conflicting duplicates.
+    fn new(old: &'a OuiDatabase, new: &'a OuiDatabase) -> Self {
+        let index = |db: &'a OuiDatabase| {
+            let mut map = HashMap::<MacPrefix, &Oui>::with_capacity(db.len());
+            for oui in db.entries() {
+                map.entry(oui.mac_prefix).or_insert(oui);
+            }
+            map
+        };
+        let (old_index, new_index) = (index(old), index(new));
+
+        let mut diff = Self::default();
+        for oui in old.entries() {
+            if !std::ptr::eq(old_index[&oui.mac_prefix], oui) {
+                continue;
+            }
+            match new_index.get(&oui.mac_prefix) {
+                None => diff.removed.push(oui),
+                Some(new_oui)
+                    if new_oui.long_name() != oui.long_name()
+                        || new_oui.short_name() != oui.short_name() =>
+                {
+                    diff.renamed.push((oui, new_oui))
+                }
+                Some(_) => (),
+            }
+        }
+        for oui in new.entries() {
+            if std::ptr::eq(new_index[&oui.mac_prefix], oui)
+                && !old_index.contains_key(&oui.mac_prefix)
+            {
+                diff.added.push(oui);
+            }
+        }
+        diff
+    }
+
+    fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        for oui in &self.added {
+            writeln!(out, "+ {}\t{}", oui.mac_prefix, oui.long_name())?;
+        }
+        for oui in &self.removed {
+            writeln!(out, "- {}\t{}", oui.mac_prefix, oui.long_name())?;
+        }
+        // a line for each name which changed, so that a new short name isn't shown as the same
+        // long name on both sides
+        for (old, new) in &self.renamed {
+            let prefix = old.mac_prefix;
+            if old.long_name() != new.long_name() {
+                writeln!(out, "~ {prefix}\t{} -> {}", old.long_name(), new.long_name())?;
+            }
+            if old.short_name() != new.short_name() {
+                writeln!(
+                    out,
+                    "~ {prefix}\tshort name {} -> {}",
+                    old.short_name(),
+                    new.short_name()
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Print the differences between two database files
+pub fn run(old: &Path, new: &Path) -> anyhow::Result<()> {
+    let old = db::load_file(old)?;
+    let new = db::load_file(new)?;
+    let mut stdout = io::stdout().lock();
+    Diff::new(&old, &new).write(&mut stdout)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let old = OuiDatabase::from_manuf(
+            "\
+00:50:F1\tMaxlinear\tMaxlinear, Inc
+00:50:F4\tSigmatek\tSIGMATEK GMBH & CO. KG
+",
+        );
+        let new = OuiDatabase::from_manuf(
+            "\
+00:50:F1\tMaxlinear\tMaxLinear Corporation
+00:50:F4\tSIGMATEK\tSIGMATEK GMBH & CO. KG
+00:55:DA:50/28\tNanoleaf\tNanoleaf
+",
+        );
+        let diff = Diff::new(&old, &new);
+        let mut out = Vec::new();
+        diff.write(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "+ 00:55:da:50/28\tNanoleaf\n\
+             ~ 00:50:f1/24\tMaxlinear, Inc -> MaxLinear Corporation\n\
+             ~ 00:50:f4/24\tshort name Sigmatek -> SIGMATEK\n"
+        );
+
+        // and in reverse
+        let diff = Diff::new(&new, &old);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].short_name(), "Nanoleaf");
+        assert!(diff.added.is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use anyhow::Context as _;
+use oui_lookup::oui::{MacAddress, Oui};
+
+use crate::db;
+use crate::{CacheArgs, DumpFormat};
+
+/// A line of dump output
+#[derive(Debug, PartialEq, Eq)]
+enum Block<'a> {
+    /// A single database entry
+    Entry(&'a Oui),
+    /// A run of contiguous entries with the same vendor
+    Range { first: MacAddress, last: MacAddress, oui: &'a Oui },
+}
+
+/// Merge runs of adjacent same-vendor entries into address ranges.
+///
+/// Entries are only merged when each one starts right after the previous one ends and their long
+/// names are identical, so nested or conflicting entries always break a run.
+fn coalesce(entries: &[Oui]) -> Vec<Block<'_>> {
+    entries
+        .chunk_by(|a, b| {
+            a.long_name() == b.long_name() && a.mac_prefix.last().to_u64() + 1 == b.mac().to_u64()
+        })
+        .map(|run| match run {
+            [oui] => Block::Entry(oui),
+            [oui, .., last] => Block::Range { first: oui.mac(), last: last.mac_prefix.last(), oui },
+            [] => unreachable!("chunk_by never yields empty chunks"),
+        })
+        .collect()
+}
+
+fn write_block(out: &mut impl Write, block: &Block) -> io::Result<()> {
+    match block {
+        Block::Entry(oui) => {
+            writeln!(out, "{}\t{}\t{}", oui.mac_prefix, oui.short_name(), oui.long_name())
+        }
+        Block::Range { first, last, oui } => {
+            writeln!(out, "{first}-{last}\t{}\t{}", oui.short_name(), oui.long_name())
+        }
+    }
+}
+
+fn write_manuf(out: impl Write, entries: &[Oui], coalesce_ranges: bool) -> io::Result<()> {
+    let mut out = io::BufWriter::new(out);
+    if coalesce_ranges {
+        for block in coalesce(entries) {
+            write_block(&mut out, &block)?;
+        }
+    } else {
+        for oui in entries {
+            write_block(&mut out, &Block::Entry(oui))?;
+        }
+    }
+    out.flush()
+}
+
+/// Write every entry to an `oui` table, replacing any previous one.
+///
+/// `base_mac` is the prefix's first address as an integer, and is indexed so that range queries
+/// like `WHERE base_mac BETWEEN ? AND ?` are fast.
+#[cfg(feature = "sqlite")]
+fn write_sqlite(conn: &mut rusqlite::Connection, entries: &[Oui]) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute_batch(
+        "DROP TABLE IF EXISTS oui;
+         CREATE TABLE oui (
+             prefix TEXT NOT NULL,
+             prefix_len INTEGER NOT NULL,
+             base_mac INTEGER NOT NULL,
+             short_name TEXT NOT NULL,
+             long_name TEXT NOT NULL
+         );
+         CREATE INDEX oui_base_mac ON oui (base_mac);",
+    )?;
+    {
+        let mut insert = tx.prepare("INSERT INTO oui VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        for oui in entries {
+            insert.execute(rusqlite::params![
+                oui.mac_prefix.to_string(),
+                oui.prefix_len(),
+                // a 48-bit address always fits in SQLite's signed 64-bit integers
+                oui.mac().to_u64() as i64,
+                oui.short_name(),
+                oui.long_name(),
+            ])?;
+        }
+    }
+    tx.commit()
+}
+
+#[cfg(feature = "sqlite")]
+fn dump_sqlite(path: &Path, entries: &[Oui]) -> anyhow::Result<()> {
+    let mut conn = rusqlite::Connection::open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    write_sqlite(&mut conn, entries).with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn dump_sqlite(_path: &Path, _entries: &[Oui]) -> anyhow::Result<()> {
+    anyhow::bail!("SQLite output isn't supported, oui-lookup was built without the sqlite feature")
+}
+
+/// Write every database entry to stdout or a file, optionally coalescing contiguous same-vendor
+/// prefixes
+pub fn run(
+    cache_args: &CacheArgs,
+    coalesce_ranges: bool,
+    format: DumpFormat,
+    output: Option<&Path>,
+) -> anyhow::Result<()> {
+    if format == DumpFormat::Sqlite && coalesce_ranges {
+        anyhow::bail!("--coalesce only applies to manuf output");
+    }
+    let db = db::load(cache_args)?;
+    match (format, output) {
+        (DumpFormat::Manuf, None) => {
+            write_manuf(io::stdout().lock(), db.entries(), coalesce_ranges)?
+        }
+        (DumpFormat::Manuf, Some(path)) => {
+            let file = File::create(path)
+                .with_context(|| format!("failed to create {}", path.display()))?;
+            write_manuf(file, db.entries(), coalesce_ranges)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        (DumpFormat::Sqlite, Some(path)) => dump_sqlite(path, db.entries())?,
+        (DumpFormat::Sqlite, None) => anyhow::bail!("--format sqlite requires --output"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use oui_lookup::database::OuiDatabase;
+
+    #[test]
+    fn test_coalesce() {
+        let db = OuiDatabase::from_manuf(
+            "\
+00:50:F1\tMaxlinear\tMaxlinear, Inc
+00:50:F2\tMaxlinear\tMaxlinear, Inc
+00:50:F3\tSigmatek\tSIGMATEK GMBH & CO. KG
+00:50:F5\tSigmatek\tSIGMATEK GMBH & CO. KG
+",
+        );
+        let mut out = Vec::new();
+        for block in coalesce(db.entries()) {
+            write_block(&mut out, &block).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "00:50:f1:00:00:00-00:50:f2:ff:ff:ff\tMaxlinear\tMaxlinear, Inc\n\
+             00:50:f3/24\tSigmatek\tSIGMATEK GMBH & CO. KG\n\
+             00:50:f5/24\tSigmatek\tSIGMATEK GMBH & CO. KG\n"
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite() {
+        let db = OuiDatabase::from_manuf(
+            "\
+00:00:0C\tCisco\tCisco Systems, Inc
+00:50:F1\tMaxlinear\tMaxlinear, Inc
+00:55:DA:50:00:00/28\tNested\tNested Vendor
+",
+        );
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        write_sqlite(&mut conn, db.entries()).unwrap();
+        // a second dump replaces the table rather than adding to it
+        write_sqlite(&mut conn, db.entries()).unwrap();
+
+        let count = conn.query_row("SELECT COUNT(*) FROM oui", [], |row| row.get::<_, i64>(0));
+        assert_eq!(count.unwrap(), 3);
+
+        // which entry covers 00:55:da:5f:a1:23?
+        let mac = MacAddress::parse("00:55:da:5f:a1:23").unwrap().to_u64() as i64;
+        let row = conn
+            .query_row(
+                "SELECT prefix, prefix_len, short_name, long_name FROM oui
+                 WHERE base_mac <= ?1 AND ?1 < base_mac + (1 << (48 - prefix_len))
+                 ORDER BY prefix_len DESC LIMIT 1",
+                [mac],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            row,
+            (
+                String::from("00:55:da:50/28"),
+                28u8,
+                String::from("Nested"),
+                String::from("Nested Vendor")
+            )
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::io::{BufRead, Write};
+use std::sync::LazyLock;
+
+use anyhow::Context as _;
+use regex::Regex;
+
+use crate::output::Printer;
+use oui_lookup::oui::{InputFormat, MacAddress, Oui};
+
+/// Colon, dash, and Cisco dotted addresses, not touching other hex digits or separators
+static MAC_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"(?i)(?:^|[^0-9a-f:.-])(",
+        r"(?:[0-9a-f]{2}:){5}[0-9a-f]{2}|",
+        r"(?:[0-9a-f]{2}-){5}[0-9a-f]{2}|",
+        r"(?:[0-9a-f]{4}\.){2}[0-9a-f]{4}",
+        // allow a period at the end of a sentence
+        r")(?:$|[^0-9a-f:.-]|\.(?:$|[^0-9a-f]))",
+    ))
+    .unwrap()
+});
+
+/// Find the MAC addresses in a line of text
+fn find_macs(line: &str) -> Vec<MacAddress> {
+    // the characters around a match are consumed, so resume at the end of the address itself to
+    // find addresses separated by a single character
+    let mut macs = Vec::new();
+    let mut start = 0;
+    while let Some(caps) = MAC_RE.captures_at(line, start) {
+        let m = caps.get(1).unwrap();
+        macs.extend(MacAddress::parse_format(m.as_str(), InputFormat::Auto));
+        start = m.end();
+    }
+    macs
+}
+
+/// Read text and print each line containing MAC addresses, followed by the indented result for
+/// each address.
+///
+/// Lines are only printed when at least one of their results isn't filtered out.
+pub fn run<'a>(
+    reader: impl BufRead,
+    printer: Printer<impl Write>,
+    lookup: impl Fn(MacAddress) -> Option<&'a Oui>,
+) -> anyhow::Result<()> {
+    let mut printer = printer.with_indent("    ");
+    for line in reader.lines() {
+        let line = line.context("failed to read input")?;
+        let results = find_macs(&line)
+            .into_iter()
+            .map(|mac| (mac, lookup(mac)))
+            .filter(|&(mac, found)| printer.shows(mac, found))
+            .collect::<Vec<_>>();
+        if results.is_empty() {
+            continue;
+        }
+        printer.echo(&line)?;
+        for (mac, found) in results {
+            printer.result(mac, found)?;
+        }
+    }
+    printer.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::OutputArgs;
+    use oui_lookup::database::OuiDatabase;
+
+    fn extract(args: &OutputArgs, input: &str) -> String {
+        let db = OuiDatabase::from_manuf(
+            "00:00:0C\tCisco\tCisco Systems, Inc\n00:50:F1\tMaxlinear\tMaxlinear, Inc\n",
+        );
+        let mut out = Vec::new();
+        run(input.as_bytes(), Printer::new(&mut out, args), |mac| db.lookup(mac)).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_extract() {
+        let log = "\
+Oct 16 10:00:01 host dhcpd: DHCPACK on 10.0.0.5 to 00:50:f1:12:34:56. Done
+Oct 16 10:00:02 host kernel: link up, nothing to see here
+Oct 16 10:00:03 host sw1: moved 0000.0c12.3456 from port 1 (was 00-11-22-33-44-55)
+Oct 16 10:00:04 host app: id 00:50:f1:12:34:56:78 and hash deadbeefcafe aren't addresses
+";
+        assert_eq!(
+            extract(&OutputArgs::default(), log),
+            "\
+Oct 16 10:00:01 host dhcpd: DHCPACK on 10.0.0.5 to 00:50:f1:12:34:56. Done
+    00:50:f1:12:34:56 - 00:50:f1/24 - Maxlinear, Inc
+Oct 16 10:00:03 host sw1: moved 0000.0c12.3456 from port 1 (was 00-11-22-33-44-55)
+    00:00:0c:12:34:56 - 00:00:0c/24 - Cisco Systems, Inc
+    00:11:22:33:44:55 - no matching OUI found
+"
+        );
+
+        // lines with every result filtered out aren't printed at all
+        let args = OutputArgs {
+            short: true,
+            exclude_vendor: vec![String::from("maxlinear")],
+            ..Default::default()
+        };
+        assert_eq!(
+            extract(&args, log),
+            "\
+Oct 16 10:00:03 host sw1: moved 0000.0c12.3456 from port 1 (was 00-11-22-33-44-55)
+    00:00:0c:12:34:56 - 00:00:0c/24 - Cisco
+    00:11:22:33:44:55 - no matching OUI found
+"
+        );
+    }
+
+    #[test]
+    fn test_extract_sanitized() {
+        let log = "\x1b[2J\tcleared\rby 00:50:f1:12:34:56\n";
+        assert_eq!(
+            extract(&OutputArgs::default(), log),
+            "\\u{1b}[2J\tcleared\\rby 00:50:f1:12:34:56\n    \
+             00:50:f1:12:34:56 - 00:50:f1/24 - Maxlinear, Inc\n"
+        );
+    }
+}
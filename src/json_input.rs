@@ -0,0 +1,74 @@
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::Context as _;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// An input entry which couldn't be looked up, reported in the output rather than aborting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidInput {
+    /// The entry as given, or its JSON text if it wasn't a string
+    pub input: String,
+    pub error: String,
+}
+
+/// An element of the input array, either an address string or an object with a "mac" field.
+///
+/// Other fields of objects are ignored, so the output of other tools can be fed in as is.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Mac(String),
+    Object { mac: String },
+}
+
+/// Parse a JSON array of addresses, returning each address string in order.
+///
+/// Only a document which isn't a JSON array is an error, malformed elements are returned as
+/// `InvalidInput`s in their place.
+pub fn parse(data: &str) -> anyhow::Result<Vec<Result<String, InvalidInput>>> {
+    let values = serde_json::from_str::<Vec<Value>>(data).context("expected a JSON array")?;
+    Ok(values
+        .into_iter()
+        .map(|value| match Entry::deserialize(&value) {
+            Ok(Entry::Mac(mac) | Entry::Object { mac }) => Ok(mac),
+            Err(_) => Err(InvalidInput {
+                input: value.to_string(),
+                error: String::from(
+                    "expected a MAC address string or an object with a \"mac\" field",
+                ),
+            }),
+        })
+        .collect())
+}
+
+/// Read and parse a JSON array of addresses from a file, or stdin if the path is "-"
+pub fn read(path: &Path) -> anyhow::Result<Vec<Result<String, InvalidInput>>> {
+    let data = if path == Path::new("-") {
+        let mut data = String::new();
+        std::io::stdin().read_to_string(&mut data).context("failed to read stdin")?;
+        data
+    } else {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?
+    };
+    parse(&data).with_context(|| format!("invalid JSON input {}", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let entries =
+            parse(r#"["00:50:f1:12:34:56", {"mac": "00-00-0c-00-00-01", "x": 1}, 42]"#).unwrap();
+        assert_eq!(entries[0], Ok(String::from("00:50:f1:12:34:56")));
+        assert_eq!(entries[1], Ok(String::from("00-00-0c-00-00-01")));
+        assert_eq!(entries[2].as_ref().unwrap_err().input, "42");
+
+        assert!(parse(r#"{"mac": "00:50:f1:12:34:56"}"#).is_err());
+        assert!(parse("[").is_err());
+    }
+}
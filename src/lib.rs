@@ -0,0 +1,13 @@
+//! Types for looking up MAC addresses in Wireshark's OUI manuf database.
+
+#[cfg(feature = "async")]
+mod async_load;
+pub mod cache;
+pub mod database;
+pub mod oui;
+pub mod special;
+pub mod trie;
+pub mod wka;
+
+#[cfg(feature = "async")]
+pub use async_load::load_async;
@@ -0,0 +1,114 @@
+use std::io::Write;
+
+use oui_lookup::oui::{MacAddress, Oui};
+
+use crate::output::Printer;
+
+/// A network interface on this machine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interface {
+    pub name: String,
+    pub mac: MacAddress,
+}
+
+/// List the network interfaces with a hardware address, sorted by name.
+///
+/// Interfaces without an Ethernet-style address, like loopback and tunnels, are skipped.
+#[cfg(target_os = "linux")]
+pub fn interfaces() -> anyhow::Result<Vec<Interface>> {
+    use anyhow::Context as _;
+
+    let dir = std::path::Path::new("/sys/class/net");
+    let mut interfaces = Vec::new();
+    for entry in
+        std::fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?
+    {
+        let entry = entry.with_context(|| format!("failed to read {}", dir.display()))?;
+        // interfaces can disappear while listing them, and some have no address file
+        let Ok(address) = std::fs::read_to_string(entry.path().join("address")) else {
+            continue;
+        };
+        let Some(mac) = MacAddress::parse(address.trim()) else {
+            continue;
+        };
+        if mac.to_u64() != 0 {
+            interfaces
+                .push(Interface { name: entry.file_name().to_string_lossy().into_owned(), mac });
+        }
+    }
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(interfaces)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn interfaces() -> anyhow::Result<Vec<Interface>> {
+    anyhow::bail!("listing network interfaces isn't supported on this platform")
+}
+
+/// Print the result for each interface's address, labeled with the interface name
+pub fn run<'a>(
+    mut printer: Printer<impl Write>,
+    interfaces: &[Interface],
+    lookup: impl Fn(MacAddress) -> Option<&'a Oui>,
+) -> std::io::Result<()> {
+    for Interface { name, mac } in interfaces {
+        printer.labeled_result(name, *mac, lookup(*mac))?;
+    }
+    printer.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::OutputArgs;
+    use oui_lookup::database::OuiDatabase;
+
+    #[test]
+    fn test_interfaces() {
+        // whatever this machine has, listing them works and never includes an all-zero address
+        if let Ok(interfaces) = interfaces() {
+            assert!(interfaces.iter().all(|iface| iface.mac.to_u64() != 0));
+            assert!(interfaces.is_sorted_by(|a, b| a.name <= b.name));
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let db = OuiDatabase::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc\n");
+        let interfaces = [
+            ("eth0", "00:50:f1:12:34:56"),
+            ("eth1", "00:11:22:33:44:55"),
+            ("wlan0", "02:50:f1:12:34:56"),
+        ]
+        .map(|(name, mac)| Interface {
+            name: name.to_string(),
+            mac: MacAddress::parse(mac).unwrap(),
+        });
+        let local = |args: &OutputArgs| {
+            let mut out = Vec::new();
+            run(Printer::new(&mut out, args), &interfaces, |mac| db.lookup(mac)).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(
+            local(&OutputArgs::default()),
+            "\
+eth0: 00:50:f1:12:34:56 - 00:50:f1/24 - Maxlinear, Inc
+eth1: 00:11:22:33:44:55 - no matching OUI found
+wlan0: 02:50:f1:12:34:56 - locally-administered (likely randomized)
+"
+        );
+
+        // the output options apply like for any other lookup
+        let args = OutputArgs { name_only: true, short: true, ..Default::default() };
+        assert_eq!(local(&args), "Maxlinear\n\n\n");
+        let args =
+            OutputArgs { exclude_vendor: vec![String::from("maxlinear")], ..Default::default() };
+        assert!(!local(&args).contains("eth0"));
+        let args = OutputArgs { json: true, misses_only: true, ..Default::default() };
+        let json = serde_json::from_str::<serde_json::Value>(&local(&args)).unwrap();
+        assert_eq!(json[0]["label"], "eth1");
+        assert_eq!(json[1]["label"], "wlan0");
+        assert!(json[2].is_null());
+    }
+}
@@ -1,9 +1,10 @@
 use std::cmp;
 use std::fmt;
+use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct MacAddress([u8; 6]);
 
 impl fmt::Debug for MacAddress {
@@ -22,6 +23,20 @@ impl fmt::Display for MacAddress {
     }
 }
 
+impl From<[u8; 6]> for MacAddress {
+    #[inline]
+    fn from(octets: [u8; 6]) -> Self {
+        Self(octets)
+    }
+}
+
+impl From<MacAddress> for [u8; 6] {
+    #[inline]
+    fn from(mac: MacAddress) -> Self {
+        mac.0
+    }
+}
+
 /// Generic error type used for indicating failure when parsing MAC addresses.
 ///
 /// This is because clap's ValueParser magic for FromStr types requires that the type's Err type
@@ -41,7 +56,90 @@ impl std::str::FromStr for MacAddress {
     type Err = MacAddressParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Self::parse(s).ok_or(MacAddressParseError)
+        Self::parse_format(s, InputFormat::Auto).ok_or(MacAddressParseError)
+    }
+}
+
+/// Broad classification of a MAC address based on its flag bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Category {
+    /// A unicast, universally administered address, i.e. one assigned by a vendor
+    VendorAssigned,
+    /// A unicast, locally administered address, which is likely randomized
+    LocallyAdministered,
+    /// A group address, other than broadcast
+    Multicast,
+    /// The all-ones broadcast address
+    Broadcast,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::VendorAssigned => "vendor-assigned",
+            Self::LocallyAdministered => "locally-administered (likely randomized)",
+            Self::Multicast => "multicast",
+            Self::Broadcast => "broadcast",
+        })
+    }
+}
+
+/// Textual formats accepted when parsing a MAC address.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum InputFormat {
+    /// Detect the format based on which separator is used
+    #[default]
+    Auto,
+    /// Colon-separated octets, e.g. 00:50:f1:aa:bb:cc
+    Colon,
+    /// Dash-separated octets, e.g. 00-50-f1-aa-bb-cc
+    Dash,
+    /// Dot-separated groups of 16 bits, e.g. 0050.f1aa.bbcc
+    Cisco,
+    /// Hex digits with no separators, e.g. 0050f1aabbcc
+    Bare,
+    /// Space-separated bytes from a packet, e.g. an Ethernet header "ff ff ff ff ff ff 00 50 f1 ..."
+    HexDump,
+}
+
+impl InputFormat {
+    /// Strip parentheses, brackets, braces, or quotes wrapped around an address, as tools often
+    /// print it like `(00:50:f1:aa:bb:cc)`.
+    ///
+    /// Only matching pairs are removed, and only in auto-detect mode, explicit formats stay
+    /// strict.
+    pub fn strip_delimiters(mut s: &str) -> &str {
+        const PAIRS: [(char, char); 6] =
+            [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>'), ('"', '"'), ('\'', '\'')];
+        s = s.trim();
+        while let Some(inner) = PAIRS.iter().find_map(|&(open, close)| {
+            s.strip_prefix(open).and_then(|rest| rest.strip_suffix(close))
+        }) {
+            s = inner.trim();
+        }
+        s
+    }
+
+    /// Guess the format of a MAC address string. Never returns `Auto`.
+    ///
+    /// Strings containing whitespace are treated as hex dumps. Strings without any separators are
+    /// treated as a single colon-format octet if they're at most two characters long, otherwise as
+    /// bare hex digits.
+    pub fn detect(s: &str) -> Self {
+        if s.trim().contains(char::is_whitespace) {
+            Self::HexDump
+        } else if s.contains(':') {
+            Self::Colon
+        } else if s.contains('-') {
+            Self::Dash
+        } else if s.contains('.') {
+            Self::Cisco
+        } else if s.len() <= 2 {
+            Self::Colon
+        } else {
+            Self::Bare
+        }
     }
 }
 
@@ -49,15 +147,57 @@ impl MacAddress {
     /// Parse a MAC address string.
     ///
     /// If the address is truncated, then the last octets will be zero, e.g. `"aa:bb:cc"` parses to
-    /// `aa:bb:cc:00:00:00`. Returns `None` on any parsing failure, including invalid characters,
-    /// whitespace, or too many octets.
+    /// `aa:bb:cc:00:00:00`. Octets may drop their leading zeros as some Unix tools print them, so
+    /// `"0:50:f1:a:b:c"` is the same as `"00:50:f1:0a:0b:0c"`. Returns `None` on any parsing
+    /// failure, including invalid characters, whitespace, empty octets, or too many octets.
     pub fn parse(s: &str) -> Option<Self> {
-        if !s.bytes().all(|b| b.is_ascii_hexdigit() || b == b':') {
+        Self::parse_octets(s, ':')
+    }
+
+    /// Parse a MAC address string in the given format.
+    ///
+    /// Like [`parse`](Self::parse), truncated addresses are zero-filled. In the bare format, the
+    /// digits are read as nibbles from the left, so `"0050f"` parses to `00:50:f0:00:00:00`.
+    pub fn parse_format(s: &str, format: InputFormat) -> Option<Self> {
+        match format {
+            InputFormat::Auto => {
+                let s = InputFormat::strip_delimiters(s);
+                Self::parse_format(s, InputFormat::detect(s))
+            }
+            InputFormat::Colon => Self::parse_octets(s, ':'),
+            InputFormat::Dash => Self::parse_octets(s, '-'),
+            InputFormat::Cisco => Self::parse_cisco(s),
+            InputFormat::Bare => Self::parse_bare(s),
+            InputFormat::HexDump => Self::parse_hex_dump(s, 0),
+        }
+    }
+
+    /// Parse six bytes starting at `offset` from a whitespace-separated hex dump.
+    ///
+    /// This is useful for raw Ethernet frames, where the destination MAC is at offset 0 and the
+    /// source MAC at offset 6. Each byte must be exactly two hex digits, and bytes after the
+    /// address are ignored.
+    pub fn parse_hex_dump(s: &str, offset: usize) -> Option<Self> {
+        let mut bytes = s.split_whitespace().skip(offset);
+        let mut octets = [0u8; 6];
+        for octet in &mut octets {
+            let b = bytes.next()?;
+            if b.len() != 2 || !b.bytes().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+            *octet = u8::from_str_radix(b, 16).ok()?;
+        }
+        Some(Self(octets))
+    }
+
+    /// Parse up to six hex octets separated by `sep`
+    fn parse_octets(s: &str, sep: char) -> Option<Self> {
+        if !s.chars().all(|c| c.is_ascii_hexdigit() || c == sep) {
             return None;
         }
 
         let mut octets = [0u8; 6];
-        for (i, bs) in s.split(':').enumerate() {
+        for (i, bs) in s.split(sep).enumerate() {
             if i >= octets.len() {
                 return None;
             }
@@ -66,6 +206,95 @@ impl MacAddress {
         Some(Self(octets))
     }
 
+    /// Parse up to three dot-separated groups of up to four hex digits each
+    fn parse_cisco(s: &str) -> Option<Self> {
+        if !s.bytes().all(|b| b.is_ascii_hexdigit() || b == b'.') {
+            return None;
+        }
+
+        let mut octets = [0u8; 6];
+        for (i, group) in s.split('.').enumerate() {
+            if i >= 3 {
+                return None;
+            }
+            let val = u16::from_str_radix(group, 16).ok()?;
+            octets[i * 2..i * 2 + 2].copy_from_slice(&val.to_be_bytes());
+        }
+        Some(Self(octets))
+    }
+
+    /// Parse up to twelve hex digits with no separators
+    fn parse_bare(s: &str) -> Option<Self> {
+        if s.is_empty() || s.len() > 12 {
+            return None;
+        }
+
+        let mut octets = [0u8; 6];
+        for (i, c) in s.chars().enumerate() {
+            let nibble = c.to_digit(16)? as u8;
+            octets[i / 2] |= if i % 2 == 0 { nibble << 4 } else { nibble };
+        }
+        Some(Self(octets))
+    }
+
+    pub const BROADCAST: Self = Self([0xff; 6]);
+
+    /// Get the six octets of this address.
+    ///
+    /// ```
+    /// use oui_lookup::oui::MacAddress;
+    ///
+    /// let mac = MacAddress::from([0x00, 0x50, 0xf1, 0x0a, 0x0b, 0x0c]);
+    /// assert_eq!(mac.octets(), [0x00, 0x50, 0xf1, 0x0a, 0x0b, 0x0c]);
+    /// assert_eq!(mac.to_string(), "00:50:f1:0a:0b:0c");
+    /// ```
+    #[inline]
+    pub fn octets(self) -> [u8; 6] {
+        self.0
+    }
+
+    /// Borrow the octets of this address as a byte slice
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Is the I/G (individual/group) bit set, i.e. is this a multicast or broadcast address?
+    #[inline]
+    pub fn is_group(self) -> bool {
+        self.0[0] & 0x01 != 0
+    }
+
+    /// Get the individual (unicast) form of this address, with the I/G bit cleared
+    #[inline]
+    pub fn to_individual(self) -> Self {
+        let mut octets = self.0;
+        octets[0] &= !0x01;
+        Self(octets)
+    }
+
+    /// Is the U/L (universal/local) bit set, i.e. is this a locally administered address?
+    #[inline]
+    pub fn is_local(self) -> bool {
+        self.0[0] & 0x02 != 0
+    }
+
+    /// Classify this address based on its I/G and U/L bits.
+    ///
+    /// Group addresses take precedence over the U/L bit, since multicast addresses like
+    /// `33:33:xx:xx:xx:xx` set both.
+    pub fn category(self) -> Category {
+        if self == Self::BROADCAST {
+            Category::Broadcast
+        } else if self.is_group() {
+            Category::Multicast
+        } else if self.is_local() {
+            Category::LocallyAdministered
+        } else {
+            Category::VendorAssigned
+        }
+    }
+
     /// Get this MAC address in the LSB 48 bits of a u64.
     ///
     /// The upper 16 bits will always be zeroes.
@@ -83,9 +312,62 @@ impl MacAddress {
         b6.copy_from_slice(&b8[2..8]);
         Self(b6)
     }
+
+    /// Make a random address within a prefix, keeping the prefix bits exactly and taking the rest
+    /// from the low bits of `random`.
+    ///
+    /// With a uniformly random `random`, like `rand::random::<u64>()`, the address is uniformly
+    /// distributed within the prefix.
+    pub fn random_in(prefix: MacPrefix, random: u64) -> Self {
+        let host_bits = !MacPrefix::mask(prefix.prefix_len()) & Self::MAX_U64;
+        Self::from_u64(prefix.mac().to_u64() | (random & host_bits))
+    }
+
+    /// The largest address value, `ff:ff:ff:ff:ff:ff`
+    const MAX_U64: u64 = 0x0000_ffff_ffff_ffff;
+
+    /// Get the following address, or `None` after `ff:ff:ff:ff:ff:ff`
+    pub fn next(self) -> Option<Self> {
+        self.offset(1)
+    }
+
+    /// Add a signed offset to this address, or `None` if the result isn't a 48-bit address.
+    /// There's no wraparound in either direction.
+    pub fn offset(self, n: i64) -> Option<Self> {
+        let val = self.to_u64().checked_add_signed(n)?;
+        (val <= Self::MAX_U64).then(|| Self::from_u64(val))
+    }
 }
 
-#[derive(Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+/// IEEE MAC address block registries, which differ by block size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Registry {
+    /// MAC Address Block Large, a 24 bit OUI
+    #[serde(rename = "MA-L")]
+    MaL,
+    /// MAC Address Block Medium, a 28 bit prefix
+    #[serde(rename = "MA-M")]
+    MaM,
+    /// MAC Address Block Small, a 36 bit prefix.
+    ///
+    /// This also covers the legacy Individual Address Blocks (IABs), which were 36 bit blocks
+    /// under a handful of IEEE-owned OUIs like 00:50:C2. Manuf files list them as ordinary /36
+    /// entries, so they need no special handling.
+    #[serde(rename = "MA-S")]
+    MaS,
+}
+
+impl fmt::Display for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::MaL => "MA-L",
+            Self::MaM => "MA-M",
+            Self::MaS => "MA-S",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct MacPrefix {
     /// Packed MAC address and prefix length.
     ///
@@ -140,7 +422,9 @@ impl cmp::PartialOrd for MacPrefix {
 }
 
 impl MacPrefix {
-    /// Get a mask for the given prefix length
+    /// Get a mask for the given prefix length.
+    ///
+    /// A length of zero gives an empty mask, so a `/0` prefix matches every address.
     fn mask(prefix_len: u8) -> u64 {
         debug_assert!(prefix_len <= 48);
         ((1u64 << prefix_len) - 1) << (48 - prefix_len)
@@ -149,19 +433,118 @@ impl MacPrefix {
     /// Parse a MAC prefix string.
     ///
     /// One to six colon-separated hex octets, optionally followed by a `/` and a prefix length. If
-    /// the prefix length is omitted, it's assumed to be 24 bits (3 octets).
+    /// the prefix length is omitted, it's assumed to be 24 bits (3 octets). Lengths must be from 1
+    /// to 48, a `/0` would match every address and isn't a meaningful database entry.
+    ///
+    /// IEEE-style assignment codes of 6, 7, or 9 hex digits with no separators (like `FCFBFB`) are
+    /// also accepted, and are MA-L, MA-M, or MA-S prefixes of 24, 28, or 36 bits respectively.
     pub fn parse(s: &str) -> Option<Self> {
+        Self::parse_with_default(s, 24)
+    }
+
+    /// Parse a MAC prefix string like [`parse`](Self::parse), but with a different prefix length
+    /// used when there's no `/` suffix. Bare IEEE assignment codes always use their own length.
+    pub fn parse_with_default(s: &str, default_len: u8) -> Option<Self> {
+        if matches!(s.len(), 6 | 7 | 9) && s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            let mac = MacAddress::parse_format(s, InputFormat::Bare)?;
+            return Some(Self::from_parts(mac, s.len() as u8 * 4));
+        }
+
         let (mac, prefix_len) = match s.split_once('/') {
             Some((m, p)) => (m, p.parse::<u8>().ok()?),
-            None => (s, 24),
+            None => (s, default_len),
         };
+        if !(1..=48).contains(&prefix_len) {
+            return None;
+        }
         let mac = MacAddress::parse(mac)?;
         Some(Self::from_parts(mac, prefix_len))
     }
 
+    /// Parse a MAC prefix written in any of the common styles, for prefixes pasted from elsewhere.
+    ///
+    /// Case, wrapping brackets or quotes, and the separators don't matter, so `FC-FB-FB`,
+    /// `fcfbfb`, `fc:fb:fb`, and `FCFB.FB` are all the same /24. Without a `/` suffix the prefix
+    /// length is four bits for each hex digit given. Colon or dash separated octets may drop their
+    /// leading zeros, like in [`MacAddress::parse`].
+    pub fn parse_flexible(s: &str) -> Option<Self> {
+        let s = InputFormat::strip_delimiters(s);
+        let (s, explicit_len) = match s.split_once('/') {
+            Some((m, p)) => (m, Some(p.parse::<u8>().ok()?)),
+            None => (s, None),
+        };
+        let groups = |sep: &[char]| s.split(sep).collect::<Vec<_>>();
+        let digits = match InputFormat::detect(s) {
+            InputFormat::Colon | InputFormat::Dash => {
+                let octets = groups(&[':', '-']);
+                if !octets.iter().all(|o| (1..=2).contains(&o.len())) {
+                    return None;
+                }
+                octets.iter().map(|o| format!("{o:0>2}")).collect::<String>()
+            }
+            InputFormat::Cisco => {
+                let words = groups(&['.']);
+                let (last, full) = words.split_last()?;
+                if !full.iter().all(|w| w.len() == 4) || !(1..=4).contains(&last.len()) {
+                    return None;
+                }
+                words.concat()
+            }
+            InputFormat::HexDump => {
+                let octets = s.split_whitespace().collect::<Vec<_>>();
+                if !octets.iter().all(|o| o.len() == 2) {
+                    return None;
+                }
+                octets.concat()
+            }
+            InputFormat::Bare | InputFormat::Auto => s.to_string(),
+        };
+        if !(1..=12).contains(&digits.len()) || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let prefix_len = explicit_len.unwrap_or(digits.len() as u8 * 4);
+        if !(1..=48).contains(&prefix_len) {
+            return None;
+        }
+        let mac = MacAddress::parse_format(&digits, InputFormat::Bare)?;
+        Some(Self::from_parts(mac, prefix_len))
+    }
+
+    /// Parse a wildcard prefix string like `00:50:*`.
+    ///
+    /// One to five colon-separated hex octets followed by a `:*` which matches the rest of the
+    /// address, so the prefix length is eight bits for each octet given.
+    pub fn parse_wildcard(s: &str) -> Option<Self> {
+        let head = s.strip_suffix(":*")?;
+        let count = head.split(':').count();
+        if count >= 6 {
+            return None;
+        }
+        let mac = MacAddress::parse(head)?;
+        Some(Self::from_parts(mac, count as u8 * 8))
+    }
+
+    /// Build a prefix from an address and a length in bits, clearing the address bits beyond it.
+    ///
+    /// A `prefix_len` of zero gives an all-zero prefix which matches every address.
+    ///
+    /// # Panics
+    ///
+    /// If `prefix_len` is greater than 48.
     pub fn from_parts(mac: MacAddress, prefix_len: u8) -> Self {
+        assert!(prefix_len <= 48, "MAC prefix length {prefix_len} is longer than 48 bits");
         let val = ((prefix_len as u64) << 56) | (mac.to_u64() & Self::mask(prefix_len));
-        Self { val }
+        let prefix = Self { val };
+        debug_assert!(prefix.is_masked());
+        prefix
+    }
+
+    /// Are all the address bits beyond the prefix length clear?
+    ///
+    /// Longest-prefix matching over sorted entries relies on this, since an entry's base address
+    /// has to be the first address it covers.
+    fn is_masked(self) -> bool {
+        self.mac().to_u64() & !Self::mask(self.prefix_len()) & 0x0000_ffff_ffff_ffff == 0
     }
 
     /// Get the MAC address portion of this prefix
@@ -176,39 +559,242 @@ impl MacPrefix {
         (self.val >> 56) as u8
     }
 
+    /// Get the packed representation, the prefix length in the top byte and the masked MAC
+    /// address in the low 48 bits
+    #[inline]
+    pub fn packed(self) -> u64 {
+        self.val
+    }
+
+    /// Get the last MAC address covered by this prefix
+    pub fn last(self) -> MacAddress {
+        let host_bits = !Self::mask(self.prefix_len()) & 0x0000_ffff_ffff_ffff;
+        MacAddress::from_u64(self.mac().to_u64() | host_bits)
+    }
+
+    /// The shortest prefix length that [`addresses`](Self::addresses) will enumerate
+    pub const ENUMERABLE_PREFIX_LEN: u8 = 40;
+
+    /// Iterate over every address covered by this prefix, in order.
+    ///
+    /// Only prefixes of at least [`ENUMERABLE_PREFIX_LEN`](Self::ENUMERABLE_PREFIX_LEN) bits
+    /// (at most 256 addresses) can be enumerated, anything shorter returns `None` since even a
+    /// /24 has 16 million addresses.
+    pub fn addresses(self) -> Option<impl Iterator<Item = MacAddress>> {
+        if self.prefix_len() < Self::ENUMERABLE_PREFIX_LEN {
+            return None;
+        }
+        Some((self.mac().to_u64()..=self.last().to_u64()).map(MacAddress::from_u64))
+    }
+
+    /// Get the IEEE registry that assigns blocks of this size, if any
+    pub fn registry(self) -> Option<Registry> {
+        match self.prefix_len() {
+            24 => Some(Registry::MaL),
+            28 => Some(Registry::MaM),
+            36 => Some(Registry::MaS),
+            _ => None,
+        }
+    }
+
     /// Does this prefix match some MAC address?
     pub fn matches(self, mac: MacAddress) -> bool {
         let mask = Self::mask(self.prefix_len());
         (mac.to_u64() & mask) == (self.val & mask)
     }
+
+    /// Get the longest prefix containing both addresses, which is /48 for identical addresses and
+    /// /0 if they differ in the first bit.
+    pub fn common_prefix(a: MacAddress, b: MacAddress) -> Self {
+        // addresses are the low 48 bits of a u64, so the top 16 bits always match
+        let len = (a.to_u64() ^ b.to_u64()).leading_zeros() - 16;
+        Self::from_parts(a, len.min(48) as u8)
+    }
+
+    /// Do this prefix and another cover any of the same addresses?
+    ///
+    /// Prefixes are either nested or disjoint, so this is true when either contains the other.
+    pub fn overlaps(self, other: MacPrefix) -> bool {
+        self.matches(other.mac()) || other.matches(self.mac())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Oui {
     pub mac_prefix: MacPrefix,
-    #[allow(unused)]
-    pub short_name: String,
-    pub long_name: String,
+    /// Names are shared between entries by [`OuiDatabase`](crate::database::OuiDatabase), since
+    /// many vendors own dozens of prefixes
+    pub(crate) short_name: Arc<str>,
+    pub(crate) long_name: Arc<str>,
+    /// The organization's mailing address, only available from the IEEE CSV registry. It isn't
+    /// stored in cache files.
+    #[serde(skip)]
+    pub address: Option<String>,
+    /// Which database this entry came from, when tagged with
+    /// [`OuiDatabase::set_source`](crate::database::OuiDatabase::set_source). It isn't stored in
+    /// cache files.
+    #[serde(skip)]
+    pub source: Option<Arc<str>>,
+}
+
+/// A reader for the IEEE registry CSV, which returns the header row like any other
+pub(crate) fn ieee_csv_reader<R: std::io::Read>(data: R) -> csv::Reader<R> {
+    csv::ReaderBuilder::new().has_headers(false).flexible(true).from_reader(data)
 }
 
 impl Oui {
+    pub fn new(mac_prefix: MacPrefix, short_name: &str, long_name: &str) -> Self {
+        Self {
+            mac_prefix,
+            short_name: short_name.into(),
+            long_name: long_name.into(),
+            address: None,
+            source: None,
+        }
+    }
+
     /// Parse a single line of the wireshark `manuf` database.
+    ///
+    /// Columns may be separated by any run of tabs and spaces, and whitespace at the ends of the
+    /// line isn't part of the long name. Whitespace inside the long name is kept as written.
     pub fn from_manuf(s: &str) -> Option<Self> {
-        let s = s.trim_start();
+        let s = s.trim();
         if s.starts_with('#') {
             return None;
         }
 
-        let (mac_s, s) = s.split_at(s.find(|c: char| c.is_whitespace())?);
+        // split on chars rather than slicing at byte offsets, so multi-byte characters are safe
+        let (mac_s, s) = s.split_once(char::is_whitespace)?;
         let mac_prefix = MacPrefix::parse(mac_s)?;
+        debug_assert!(mac_prefix.is_masked(), "{mac_s} parsed to an unmasked prefix");
+
+        let (short, long) = s.trim_start().split_once(char::is_whitespace)?;
+        let long = long.trim_start();
+        if long.is_empty() {
+            return None;
+        }
+
+        Some(Self::new(mac_prefix, short, long))
+    }
+
+    /// Parse a line of Wireshark's `wka` well-known addresses file, like
+    /// `01-80-C2-00-00-0E\tLLDP_Multicast`.
+    ///
+    /// Addresses may use `-` or `:` separators, and without a `/` suffix are exact 48 bit
+    /// matches. The file has only one name, which is used for both the short and long name.
+    pub fn from_wka(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if s.starts_with('#') {
+            return None;
+        }
+
+        let (mac_s, name) = s.split_once(char::is_whitespace)?;
+        let mac_prefix = MacPrefix::parse_with_default(&mac_s.replace('-', ":"), 48)?;
+        let name = name.split('#').next()?.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let name = Arc::<str>::from(name);
+        Some(Self {
+            mac_prefix,
+            short_name: name.clone(),
+            long_name: name,
+            address: None,
+            source: None,
+        })
+    }
 
-        let (_, s) = s.split_at(s.find(|c: char| !c.is_whitespace())?);
-        let (short, s) = s.split_at(s.find(|c: char| c.is_whitespace())?);
-        let (_, long) = s.split_at(s.find(|c: char| !c.is_whitespace())?);
+    /// Parse a row of the IEEE registry CSV, like
+    /// `MA-L,0050F1,"Maxlinear, Inc",50 Parkway Carlsbad CA US 92008`.
+    ///
+    /// The columns are the registry, the hex assignment, the organization name, and its address.
+    /// The CSV has no abbreviated names, so the organization name is used for both names. Rows from
+    /// the legacy IAB registry are /36 blocks like MA-S. The header row and rows from other
+    /// registries (like CID) are rejected, as is a row with an unterminated quoted field.
+    pub fn from_ieee_csv(s: &str) -> Option<Self> {
+        // the csv crate ends an unterminated quoted field at the end of its input, which for a
+        // single row means a truncated name
+        if !s.matches('"').count().is_multiple_of(2) {
+            return None;
+        }
+        let mut reader = ieee_csv_reader(s.as_bytes());
+        Self::from_ieee_record(&reader.records().next()?.ok()?)
+    }
+
+    /// Parse a record of the IEEE registry CSV, as for `from_ieee_csv`
+    pub(crate) fn from_ieee_record(record: &csv::StringRecord) -> Option<Self> {
+        let mut fields = record.iter();
+        let (registry, assignment, name) = (fields.next()?, fields.next()?, fields.next()?);
+        let mac_prefix = MacPrefix::parse(assignment.trim())?;
+        let expected = match registry.trim() {
+            "MA-L" => Registry::MaL,
+            "MA-M" => Registry::MaM,
+            "MA-S" | "IAB" => Registry::MaS,
+            _ => return None,
+        };
+        if mac_prefix.registry() != Some(expected) {
+            return None;
+        }
+
+        let name = Arc::<str>::from(name.trim());
+        let address = fields.next().map(str::trim).filter(|a| !a.is_empty()).map(String::from);
+        Some(Self { mac_prefix, short_name: name.clone(), long_name: name, address, source: None })
+    }
+
+    /// Get the long name without corporate suffixes like ", Inc." or " Co., Ltd", and with runs
+    /// of whitespace collapsed to a single space.
+    ///
+    /// This is for grouping entries by vendor, since the same company's name is often written a few
+    /// different ways. Names which are nothing but suffixes are left alone, apart from the
+    /// whitespace.
+    pub fn normalized_vendor(&self) -> String {
+        const SUFFIXES: &[&str] = &[
+            "ag",
+            "b.v",
+            "bv",
+            "co",
+            "corp",
+            "corporation",
+            "gmbh",
+            "inc",
+            "incorporated",
+            "limited",
+            "llc",
+            "ltd",
+            "plc",
+            "s.a",
+            "sa",
+        ];
+        let collapsed = self.long_name.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut name = collapsed.as_str();
+        loop {
+            let trimmed = name.trim_end_matches([' ', ',', '.']);
+            let (rest, last) = match trimmed.rfind([' ', ',']) {
+                Some(i) => (&trimmed[..i], &trimmed[i + 1..]),
+                None => ("", trimmed),
+            };
+            let rest = rest.trim_end_matches([' ', ',']);
+            if rest.is_empty() || !SUFFIXES.contains(&last.to_lowercase().as_str()) {
+                name = trimmed;
+                break;
+            }
+            name = rest;
+        }
+        // only suffixes are left, so this was all suffixes to begin with
+        if SUFFIXES.contains(&name.to_lowercase().as_str()) {
+            return collapsed;
+        }
+        name.to_string()
+    }
+
+    #[inline]
+    pub fn short_name(&self) -> &str {
+        &self.short_name
+    }
 
-        let short_name = short.to_string();
-        let long_name = long.to_string();
-        Some(Self { mac_prefix, short_name, long_name })
+    #[inline]
+    pub fn long_name(&self) -> &str {
+        &self.long_name
     }
 
     #[inline]
@@ -216,7 +802,6 @@ impl Oui {
         self.mac_prefix.mac()
     }
 
-    #[allow(unused)]
     #[inline]
     pub fn prefix_len(&self) -> u8 {
         self.mac_prefix.prefix_len()
@@ -248,20 +833,22 @@ impl cmp::PartialOrd for Oui {
 #[cfg(test)]
 mod test {
     use super::*;
+    use proptest::prelude::*;
+    use rand::{Rng, SeedableRng};
 
     #[test]
     fn test_parse() {
         let o = Oui::from_manuf("00:50:F1           Maxlinear       Maxlinear, Inc").unwrap();
         assert_eq!(&o.mac().0, b"\x00\x50\xf1\x00\x00\x00");
         assert_eq!(o.prefix_len(), 24);
-        assert_eq!(o.short_name, "Maxlinear");
-        assert_eq!(o.long_name, "Maxlinear, Inc");
+        assert_eq!(o.short_name(), "Maxlinear");
+        assert_eq!(o.long_name(), "Maxlinear, Inc");
 
         let o = Oui::from_manuf("00:55:DA:50/28     Nanoleaf        Nanoleaf").unwrap();
         assert_eq!(&o.mac().0, b"\x00\x55\xda\x50\x00\x00");
         assert_eq!(o.prefix_len(), 28);
-        assert_eq!(o.short_name, "Nanoleaf");
-        assert_eq!(o.long_name, "Nanoleaf");
+        assert_eq!(o.short_name(), "Nanoleaf");
+        assert_eq!(o.long_name(), "Nanoleaf");
 
         assert!(Oui::from_manuf("# foo bar").is_none());
     }
@@ -276,5 +863,430 @@ mod test {
         assert!(prefix.matches(MacAddress::parse("00:1b:c5:00:10:aa").unwrap()));
         assert!(prefix.matches(MacAddress::parse("00:1b:c5:00:11:aa").unwrap()));
         assert!(!prefix.matches(MacAddress::parse("00:1b:c5:00:20:bb").unwrap()));
+        assert_eq!(prefix.last(), MacAddress::parse("00:1b:c5:00:1f:ff").unwrap());
+    }
+
+    #[test]
+    fn test_matches_all() {
+        let mac = MacAddress::parse("01:02:03:04:05:06").unwrap();
+        let prefix = MacPrefix::from_parts(mac, 0);
+        assert_eq!(prefix.mac(), MacAddress::from([0; 6]));
+        assert_eq!(prefix, MacPrefix::from_parts(MacAddress::BROADCAST, 0));
+        assert_eq!(prefix.last(), MacAddress::BROADCAST);
+        for mac in ["00:00:00:00:00:00", "01:02:03:04:05:06", "ff:ff:ff:ff:ff:ff"] {
+            assert!(prefix.matches(MacAddress::parse(mac).unwrap()));
+        }
+
+        // but it can't come from parsing
+        assert!(MacPrefix::parse("00:00:00/0").is_none());
+        assert!(MacPrefix::parse("00:00:00/49").is_none());
+        assert!(Oui::from_manuf("00:00:00/0\tEverything\tEverything").is_none());
+    }
+
+    #[test]
+    fn test_parse_bare_assignment() {
+        let prefix = MacPrefix::parse("FCFBFB").unwrap();
+        assert_eq!(prefix, MacPrefix::parse("fc:fb:fb").unwrap());
+        assert_eq!(prefix.prefix_len(), 24);
+        assert_eq!(prefix.registry(), Some(Registry::MaL));
+
+        let prefix = MacPrefix::parse("0055DA5").unwrap();
+        assert_eq!(prefix, MacPrefix::parse("00:55:da:50/28").unwrap());
+        assert_eq!(prefix.registry(), Some(Registry::MaM));
+
+        let prefix = MacPrefix::parse("70B3D5F2A").unwrap();
+        assert_eq!(prefix, MacPrefix::parse("70:b3:d5:f2:a0/36").unwrap());
+        assert_eq!(prefix.registry(), Some(Registry::MaS));
+
+        // other lengths aren't IEEE assignment sizes
+        assert!(MacPrefix::parse("FCFB").is_none());
+        assert!(MacPrefix::parse("FCFBFB00").is_none());
+        assert!(MacPrefix::parse("FCFBFG").is_none());
+    }
+
+    #[test]
+    fn test_parse_with_default() {
+        let prefix = MacPrefix::parse_with_default("00:55:da:50", 28).unwrap();
+        assert_eq!(prefix, MacPrefix::parse("00:55:da:50/28").unwrap());
+        assert_eq!(prefix.prefix_len(), 28);
+        // explicit lengths and assignment codes aren't affected
+        assert_eq!(MacPrefix::parse_with_default("00:55:da/24", 28).unwrap().prefix_len(), 24);
+        assert_eq!(MacPrefix::parse_with_default("FCFBFB", 28).unwrap().prefix_len(), 24);
+        assert!(MacPrefix::parse_with_default("00:55:da", 0).is_none());
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let p = |s| MacPrefix::parse(s).unwrap();
+        assert!(p("00:55:da").overlaps(p("00:55:da:50/28")));
+        assert!(p("00:55:da:50/28").overlaps(p("00:55:da")));
+        assert!(p("00:55:da").overlaps(p("00:55:da")));
+        assert!(!p("00:55:da:50/28").overlaps(p("00:55:da:60/28")));
+        assert!(!p("00:55:da").overlaps(p("00:55:db")));
+    }
+
+    #[test]
+    fn test_common_prefix() {
+        let mac = |s| MacAddress::parse(s).unwrap();
+        let common = |a, b| MacPrefix::common_prefix(mac(a), mac(b)).to_string();
+        assert_eq!(common("00:50:f1:12:34:56", "00:50:f1:12:34:56"), "00:50:f1:12:34:56/48");
+        assert_eq!(common("00:50:f1:12:34:56", "00:50:f1:12:34:57"), "00:50:f1:12:34:56/47");
+        assert_eq!(common("00:50:f1:12:34:56", "00:50:f1:ab:cd:ef"), "00:50:f1/24");
+        assert_eq!(common("00:55:da:50:00:01", "00:55:da:5f:ff:ff"), "00:55:da:50/28");
+        let none = MacPrefix::common_prefix(mac("00:50:f1:12:34:56"), mac("80:50:f1:12:34:56"));
+        assert_eq!(none.prefix_len(), 0);
+        assert!(none.matches(mac("ff:ff:ff:ff:ff:ff")));
+    }
+
+    #[test]
+    fn test_parse_wildcard() {
+        let prefix = MacPrefix::parse_wildcard("00:50:*").unwrap();
+        assert_eq!(prefix, MacPrefix::parse("00:50/16").unwrap());
+        assert!(prefix.matches(MacAddress::parse("00:50:f1:12:34:56").unwrap()));
+        assert!(!prefix.matches(MacAddress::parse("00:51:f1:12:34:56").unwrap()));
+
+        let prefix = MacPrefix::parse_wildcard("00:50:f1:*").unwrap();
+        assert_eq!(prefix, MacPrefix::parse("00:50:f1/24").unwrap());
+        assert_eq!(MacPrefix::parse_wildcard("0:50:f1:12:34:*").unwrap().prefix_len(), 40);
+
+        assert!(MacPrefix::parse_wildcard("*").is_none());
+        assert!(MacPrefix::parse_wildcard("00:50").is_none());
+        assert!(MacPrefix::parse_wildcard("00:*:f1").is_none());
+        assert!(MacPrefix::parse_wildcard("00:50:f1:12:34:56:*").is_none());
+    }
+
+    #[test]
+    fn test_from_manuf_malformed() {
+        assert!(Oui::from_manuf("").is_none());
+        assert!(Oui::from_manuf(" \t  ").is_none());
+        assert!(Oui::from_manuf("00:50:F1\t").is_none());
+        assert!(Oui::from_manuf("00:50:F1\tMaxlinear\t").is_none());
+        assert!(Oui::from_manuf("00:50:F1\tMaxlinear\t  \t").is_none());
+        assert!(Oui::from_manuf("00:50:F1é\tMaxlinear\tMaxlinear, Inc").is_none());
+        assert!(Oui::from_manuf("é\u{3000}é\u{3000}é").is_none());
+        assert!(Oui::from_wka("01-80-C2-00-00-0Eé\tLLDP").is_none());
+
+        // multi-byte whitespace and names are fine
+        let oui = Oui::from_manuf("00:50:F1\u{3000}Ünï\u{a0}Ünïcödé GmbH").unwrap();
+        assert_eq!(oui.short_name(), "Ünï");
+        assert_eq!(oui.long_name(), "Ünïcödé GmbH");
+        let oui = Oui::from_manuf("00:50:F1 \t Maxlinear \t Maxlinear, Inc").unwrap();
+        assert_eq!(oui.short_name(), "Maxlinear");
+        assert_eq!(oui.long_name(), "Maxlinear, Inc");
+    }
+
+    #[test]
+    fn test_from_manuf_whitespace() {
+        // an MA-S entry whose long name repeats the short name and carries a comment
+        let expected =
+            Oui::from_manuf("8C:1F:64:F5:A0:00/36\tAmpere\tAmpere Ltd (Ampere)").unwrap();
+        assert_eq!(expected.short_name(), "Ampere");
+        assert_eq!(expected.long_name(), "Ampere Ltd (Ampere)");
+        assert_eq!(expected.prefix_len(), 36);
+
+        for line in [
+            "8C:1F:64:F5:A0:00/36 Ampere Ampere Ltd (Ampere)",
+            "8C:1F:64:F5:A0:00/36    Ampere      Ampere Ltd (Ampere)",
+            "8C:1F:64:F5:A0:00/36\t\tAmpere\t\t\tAmpere Ltd (Ampere)",
+            "8C:1F:64:F5:A0:00/36 \t Ampere\t  \tAmpere Ltd (Ampere)",
+            "\t 8C:1F:64:F5:A0:00/36\t \tAmpere \t Ampere Ltd (Ampere) \t\r",
+        ] {
+            let oui = Oui::from_manuf(line).unwrap_or_else(|| panic!("{line:?} didn't parse"));
+            assert_eq!(oui.mac_prefix, expected.mac_prefix, "{line:?}");
+            assert_eq!(oui.short_name(), expected.short_name(), "{line:?}");
+            assert_eq!(oui.long_name(), expected.long_name(), "{line:?}");
+        }
+
+        // runs of whitespace inside the long name are its own, not column separators
+        let oui = Oui::from_manuf("00:50:F1 \t Maxlinear\tMaxlinear,\t Inc").unwrap();
+        assert_eq!(oui.long_name(), "Maxlinear,\t Inc");
+    }
+
+    #[test]
+    fn test_from_ieee_csv() {
+        let oui = Oui::from_ieee_csv(
+            "MA-L,0050F1,\"Maxlinear, Inc\",\"50 Parkway, \"\"Suite\"\" 1 Carlsbad CA US 92008 \"\r",
+        )
+        .unwrap();
+        assert_eq!(oui.mac_prefix, MacPrefix::parse("00:50:f1").unwrap());
+        assert_eq!(oui.long_name(), "Maxlinear, Inc");
+        assert_eq!(oui.short_name(), "Maxlinear, Inc");
+        assert_eq!(oui.address.as_deref(), Some("50 Parkway, \"Suite\" 1 Carlsbad CA US 92008"));
+
+        let oui = Oui::from_ieee_csv("MA-M,0055DA5,Nanoleaf,").unwrap();
+        assert_eq!(oui.mac_prefix, MacPrefix::parse("00:55:da:50/28").unwrap());
+        assert_eq!(oui.address, None);
+
+        assert!(
+            Oui::from_ieee_csv("Registry,Assignment,Organization Name,Organization Address")
+                .is_none()
+        );
+        assert!(Oui::from_ieee_csv("MA-L,0055DA5,Wrong length,").is_none());
+        assert!(Oui::from_ieee_csv("CID,0A1B2C,Company ID,").is_none());
+        assert!(Oui::from_ieee_csv("MA-L,0050F1,\"Unterminated").is_none());
+
+        // a whole file is read as CSV, so a quoted address may span lines
+        let db = crate::database::OuiDatabase::from_ieee_csv(
+            "Registry,Assignment,Organization Name,Organization Address\r\n\
+             MA-L,0050F1,\"Maxlinear, Inc\",\"50 Parkway\nCarlsbad CA US 92008\"\r\n\
+             CID,0A1B2C,Company ID,\r\n\
+             MA-M,0055DA5,Nanoleaf,\r\n",
+        );
+        let entries = db.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].long_name(), "Maxlinear, Inc");
+        assert_eq!(entries[0].address.as_deref(), Some("50 Parkway\nCarlsbad CA US 92008"));
+        assert_eq!(entries[1].mac_prefix, MacPrefix::parse("00:55:da:50/28").unwrap());
+    }
+
+    #[test]
+    fn test_iab() {
+        let block = MacPrefix::parse("00:50:c2:12:30:00/36").unwrap();
+        let oui = Oui::from_manuf("00:50:C2:12:30:00/36\tIABVendor\tSome IAB Vendor").unwrap();
+        assert_eq!(oui.mac_prefix, block);
+        assert_eq!(block.registry(), Some(Registry::MaS));
+
+        let oui = Oui::from_ieee_csv("IAB,0050C2123,Some IAB Vendor,Somewhere").unwrap();
+        assert_eq!(oui.mac_prefix, block);
+        assert!(Oui::from_ieee_csv("IAB,0050C2,Wrong length,").is_none());
+
+        // nested within the IEEE's own /24, the IAB is the longer match
+        let db = crate::database::OuiDatabase::from_manuf(
+            "00:50:C2\tIEEERegi\tIEEE Registration Authority\n\
+             00:50:C2:12:30:00/36\tIABVendor\tSome IAB Vendor\n",
+        );
+        let lookup = |mac| db.lookup(MacAddress::parse(mac).unwrap()).unwrap().short_name();
+        assert_eq!(lookup("00:50:c2:12:3f:ff"), "IABVendor");
+        assert_eq!(lookup("00:50:c2:12:40:00"), "IEEERegi");
+    }
+
+    #[test]
+    fn test_normalized_vendor() {
+        let normalized =
+            |name| Oui::new(MacPrefix::parse("00").unwrap(), "", name).normalized_vendor();
+        assert_eq!(normalized("Cisco Systems, Inc"), "Cisco Systems");
+        assert_eq!(normalized("Cisco Systems"), "Cisco Systems");
+        assert_eq!(normalized("Cisco  Systems, Inc."), "Cisco Systems");
+        assert_eq!(normalized("Huawei Technologies Co.,Ltd"), "Huawei Technologies");
+        assert_eq!(normalized("Intel Corporate"), "Intel Corporate");
+        assert_eq!(normalized("Microsoft Corp."), "Microsoft");
+        assert_eq!(normalized("Hon Hai Precision Ind. Co.,Ltd."), "Hon Hai Precision Ind");
+        assert_eq!(normalized("Inc."), "Inc.");
+        assert_eq!(normalized("  Co.,  Ltd "), "Co., Ltd");
+        assert_eq!(normalized("AG"), "AG");
+    }
+
+    #[test]
+    fn test_parse_abbreviated() {
+        let mac = MacAddress::parse("0:50:f1:a:b:c").unwrap();
+        assert_eq!(mac.0, [0x00, 0x50, 0xf1, 0x0a, 0x0b, 0x0c]);
+        assert_eq!(mac.to_string(), "00:50:f1:0a:0b:0c");
+        assert_eq!(MacAddress::parse(&mac.to_string()), Some(mac));
+
+        // a dropped leading zero in each position individually
+        for i in 0..6 {
+            let mut octets = ["10"; 6];
+            octets[i] = "1";
+            let mut expected = [0x10; 6];
+            expected[i] = 0x01;
+            assert_eq!(MacAddress::parse(&octets.join(":")).unwrap().0, expected);
+        }
+
+        let oui = Oui::from_manuf("00:50:F1           Maxlinear       Maxlinear, Inc").unwrap();
+        assert!(oui.mac_prefix.matches(mac));
+
+        assert!(MacAddress::parse("0:50:f1:a:b:c:d").is_none());
+        assert!(MacAddress::parse("0:50::a:b:c").is_none());
+    }
+
+    #[test]
+    fn test_octets() {
+        let octets = [0x00, 0x50, 0xf1, 0xaa, 0xbb, 0xcc];
+        let mac = MacAddress::from(octets);
+        assert_eq!(mac, MacAddress::parse("00:50:f1:aa:bb:cc").unwrap());
+        assert_eq!(mac.octets(), octets);
+        assert_eq!(mac.as_bytes(), &octets[..]);
+        assert_eq!(MacAddress::from(mac.octets()), mac);
+        assert_eq!(<[u8; 6]>::from(mac), octets);
+    }
+
+    #[test]
+    fn test_offset() {
+        let mac = |s| MacAddress::parse(s).unwrap();
+        assert_eq!(mac("00:50:f1:ff:ff:ff").next(), Some(mac("00:50:f2:00:00:00")));
+        assert_eq!(mac("ff:ff:ff:ff:ff:fe").next(), Some(MacAddress::BROADCAST));
+        assert_eq!(MacAddress::BROADCAST.next(), None);
+
+        assert_eq!(mac("00:50:f1:00:00:00").offset(-1), Some(mac("00:50:f0:ff:ff:ff")));
+        assert_eq!(mac("00:50:f1:00:00:00").offset(0x1000), Some(mac("00:50:f1:00:10:00")));
+        assert_eq!(mac("00:00:00:00:00:01").offset(-1), Some(mac("00:00:00:00:00:00")));
+        assert_eq!(mac("00:00:00:00:00:00").offset(-1), None);
+        assert_eq!(mac("00:00:00:00:00:00").offset(0xffff_ffff_ffff), Some(MacAddress::BROADCAST));
+        assert_eq!(mac("00:00:00:00:00:01").offset(0xffff_ffff_ffff), None);
+        assert_eq!(MacAddress::BROADCAST.offset(i64::MAX), None);
+        assert_eq!(MacAddress::BROADCAST.offset(i64::MIN), None);
+    }
+
+    #[test]
+    fn test_addresses() {
+        let prefix = MacPrefix::parse("01:80:c2:00:00:00/44").unwrap();
+        let addresses = prefix.addresses().unwrap().map(|mac| mac.to_string()).collect::<Vec<_>>();
+        assert_eq!(addresses.len(), 16);
+        assert_eq!(addresses[0], "01:80:c2:00:00:00");
+        assert_eq!(addresses[15], "01:80:c2:00:00:0f");
+        assert!(addresses.windows(2).all(|w| w[0] < w[1]));
+
+        let prefix = MacPrefix::parse("ff:ff:ff:ff:ff:ff/48").unwrap();
+        assert_eq!(prefix.addresses().unwrap().collect::<Vec<_>>(), [MacAddress::BROADCAST]);
+        assert_eq!(
+            MacPrefix::parse("00:50:f1:12:34/40").unwrap().addresses().unwrap().count(),
+            256
+        );
+        assert!(MacPrefix::parse("00:50:f1:12:30/36").unwrap().addresses().is_none());
+        assert!(MacPrefix::parse("00:50:f1").unwrap().addresses().is_none());
+    }
+
+    #[test]
+    fn test_category() {
+        let cat = |s: &str| MacAddress::parse(s).unwrap().category();
+        assert_eq!(cat("00:50:f1:12:34:56"), Category::VendorAssigned);
+        assert_eq!(cat("02:50:f1:12:34:56"), Category::LocallyAdministered);
+        assert_eq!(cat("da:a1:19:00:00:01"), Category::LocallyAdministered);
+        assert_eq!(cat("01:00:5e:00:00:fb"), Category::Multicast);
+        assert_eq!(cat("33:33:00:00:00:01"), Category::Multicast);
+        assert_eq!(cat("ff:ff:ff:ff:ff:ff"), Category::Broadcast);
+        assert_eq!(cat("ff:ff:ff:ff:ff:fe"), Category::Multicast);
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let expected = MacAddress::parse("00:50:f1:aa:bb:cc").unwrap();
+        for s in ["00:50:f1:aa:bb:cc", "00-50-F1-AA-BB-CC", "0050.f1aa.bbcc", "0050f1aabbcc"] {
+            assert_eq!(s.parse::<MacAddress>().unwrap(), expected, "{s}");
+        }
+
+        let parse = MacAddress::parse_format;
+        assert_eq!(parse("00-50-f1", InputFormat::Dash), MacAddress::parse("00:50:f1"));
+        assert_eq!(parse("0050.f1", InputFormat::Cisco), MacAddress::parse("00:50:00:f1"));
+        assert!(parse("00:50:f1", InputFormat::Dash).is_none());
+        assert!(parse("0050.f1aa.bbcc.dd", InputFormat::Cisco).is_none());
+        assert!(parse("0050f1aabbccd", InputFormat::Bare).is_none());
+        assert!(parse("", InputFormat::Bare).is_none());
+    }
+
+    #[test]
+    fn test_parse_delimited() {
+        let expected = MacAddress::parse("00:50:f1:aa:bb:cc");
+        for s in [
+            "(00:50:f1:aa:bb:cc)",
+            "[00-50-f1-aa-bb-cc]",
+            "\"0050.f1aa.bbcc\"",
+            "(' 0050f1aabbcc ')",
+        ] {
+            assert_eq!(MacAddress::parse_format(s, InputFormat::Auto), expected, "{s}");
+        }
+        assert!(MacAddress::parse_format("(00:50:f1:aa:bb:cc]", InputFormat::Auto).is_none());
+        assert!(MacAddress::parse_format("(00:50:f1:aa:bb:cc", InputFormat::Auto).is_none());
+        assert!(MacAddress::parse_format("(00:50:f1:aa:bb:cc)", InputFormat::Colon).is_none());
+    }
+
+    #[test]
+    fn test_parse_hex_dump() {
+        // destination, source, and EtherType of an Ethernet header
+        let header = "ff ff ff ff ff ff 00 50 f1 aa bb cc 08 00";
+        assert_eq!(InputFormat::detect(header), InputFormat::HexDump);
+        assert_eq!(header.parse::<MacAddress>().unwrap(), MacAddress::BROADCAST);
+        assert_eq!(
+            MacAddress::parse_hex_dump(header, 6).unwrap(),
+            MacAddress::parse("00:50:f1:aa:bb:cc").unwrap()
+        );
+        assert_eq!(MacAddress::parse_hex_dump("  00 50\tf1 AA\nbb cc ", 0).unwrap().0[3], 0xaa);
+
+        assert!(MacAddress::parse_hex_dump(header, 9).is_none());
+        assert!(MacAddress::parse_hex_dump("00 50 f1 a bb cc", 0).is_none());
+        assert!(MacAddress::parse_hex_dump("00 50 f1 aaa bb cc", 0).is_none());
+        assert!(MacAddress::parse_hex_dump("00 50 f1 +a bb cc", 0).is_none());
+    }
+
+    #[test]
+    fn test_parse_ambiguous() {
+        // a lone digit is a whole octet in colon format, but a nibble in bare format
+        let colon = MacAddress::parse_format("5", InputFormat::Colon).unwrap();
+        let bare = MacAddress::parse_format("5", InputFormat::Bare).unwrap();
+        assert_eq!(colon, MacAddress::parse("05").unwrap());
+        assert_eq!(bare, MacAddress::parse("50").unwrap());
+        assert_eq!(MacAddress::parse_format("5", InputFormat::Auto), Some(colon));
+
+        // too long for a single colon-format octet
+        assert!(MacAddress::parse_format("0050f1", InputFormat::Colon).is_none());
+        assert_eq!(
+            MacAddress::parse_format("0050f1", InputFormat::Bare),
+            MacAddress::parse("00:50:f1")
+        );
+    }
+
+    #[test]
+    fn test_from_manuf_masked() {
+        let oui = Oui::from_manuf("00:55:DA:5F:A1:23/28\tNested\tNested Vendor").unwrap();
+        assert_eq!(oui.mac(), MacAddress::parse("00:55:da:50:00:00").unwrap());
+        assert_eq!(oui.prefix_len(), 28);
+        assert!(oui.mac_prefix.is_masked());
+
+        let oui = Oui::from_manuf("00:50:F1:FF:FF:FF\tMaxlinear\tMaxlinear, Inc").unwrap();
+        assert_eq!(oui.mac_prefix.to_string(), "00:50:f1/24");
+    }
+
+    proptest! {
+        #[test]
+        fn test_from_manuf_always_masked(mac in any::<[u8; 6]>(), len in 1..=48u8) {
+            let mac = MacAddress::from(mac);
+            let oui = Oui::from_manuf(&format!("{mac}/{len}\tShort\tLong")).unwrap();
+            prop_assert!(oui.mac_prefix.is_masked());
+            prop_assert!(oui.mac_prefix.matches(mac));
+            prop_assert_eq!(oui.mac(), MacPrefix::from_parts(mac, len).mac());
+        }
+
+        #[test]
+        fn test_random_in(mac in any::<[u8; 6]>(), len in 0..=48u8, bits in any::<u64>()) {
+            let prefix = MacPrefix::from_parts(mac.into(), len);
+            let random = MacAddress::random_in(prefix, bits);
+            prop_assert!(prefix.matches(random), "{} isn't in {}", random, prefix);
+        }
+    }
+
+    #[test]
+    fn test_random_in_bits() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let prefix = MacPrefix::parse("00:55:da:50/28").unwrap();
+        // every host bit is randomized at some point
+        let mut seen = 0;
+        for _ in 0..100 {
+            seen |= MacAddress::random_in(prefix, rng.random()).to_u64();
+        }
+        assert_eq!(seen, 0x0055_da5f_ffff);
+        let full = MacPrefix::from_parts(MacAddress::BROADCAST, 48);
+        assert_eq!(MacAddress::random_in(full, rng.random()), MacAddress::BROADCAST);
+    }
+
+    #[test]
+    fn test_parse_flexible() {
+        let expected = MacPrefix::parse("fc:fb:fb").unwrap();
+        for s in
+            ["FC-FB-FB", "fcfbfb", "fc:fb:fb", "FCFB.FB", "[Fc:fB:fb]", "fc fb fb", "fcfbfb/24"]
+        {
+            assert_eq!(MacPrefix::parse_flexible(s), Some(expected), "{s}");
+        }
+
+        let parse = |s| MacPrefix::parse_flexible(s).map(|prefix| prefix.to_string());
+        assert_eq!(parse("0:50:f1").as_deref(), Some("00:50:f1/24"));
+        assert_eq!(parse("0055da5").as_deref(), Some("00:55:da:50/28"));
+        assert_eq!(parse("0055.da50.0/36").as_deref(), Some("00:55:da:50:00/36"));
+        assert_eq!(parse("00-55-DA-50/28").as_deref(), Some("00:55:da:50/28"));
+
+        for s in
+            ["", "fc:fb:fb:", "fcf:bfb", "fcfb.fb.fb", "fc.fbfb", "fcfbfg", "fcfbfb/49", "fcfbfb/0"]
+        {
+            assert_eq!(MacPrefix::parse_flexible(s), None, "{s}");
+        }
     }
 }
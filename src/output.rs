@@ -0,0 +1,1136 @@
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufWriter, LineWriter, Write};
+use std::num::NonZeroUsize;
+
+use anstyle::{AnsiColor, Style};
+use anyhow::Context as _;
+use serde::Serialize;
+
+use crate::OutputArgs;
+use crate::json_input::InvalidInput;
+use crate::oui::{Category, MacAddress, MacPrefix, Oui, Registry};
+
+/// Names longer than this many characters are truncated in text output
+const MAX_NAME_LEN: usize = 256;
+
+/// Make a vendor name from an untrusted database safe to print to a terminal.
+///
+/// Control characters (including newlines, tabs, and the escape character which starts ANSI
+/// sequences) are escaped like Rust string literals, and excessively long names are truncated.
+pub fn sanitize_name(name: &str) -> Cow<'_, str> {
+    if name.chars().count() <= MAX_NAME_LEN && !name.chars().any(char::is_control) {
+        return Cow::Borrowed(name);
+    }
+
+    let mut out = String::with_capacity(name.len().min(MAX_NAME_LEN + 3));
+    for (i, c) in name.chars().enumerate() {
+        if i == MAX_NAME_LEN {
+            out.push_str("...");
+            break;
+        }
+        if c.is_control() {
+            out.extend(c.escape_default());
+        } else {
+            out.push(c);
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Make a line of untrusted input text safe to print to a terminal.
+///
+/// Like [`sanitize_name`], but lines aren't truncated, and tabs are common in logs and harmless so
+/// they're kept.
+pub fn sanitize_line(line: &str) -> Cow<'_, str> {
+    let escaped = |c: char| c.is_control() && c != '\t';
+    if !line.chars().any(escaped) {
+        return Cow::Borrowed(line);
+    }
+    let mut out = String::with_capacity(line.len());
+    for c in line.chars() {
+        if escaped(c) {
+            out.extend(c.escape_default());
+        } else {
+            out.push(c);
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Does the entry's long or short vendor name contain `term`, ignoring case?
+pub fn vendor_matches(oui: &Oui, term: &str) -> bool {
+    let term = term.to_lowercase();
+    oui.long_name().to_lowercase().contains(&term)
+        || oui.short_name().to_lowercase().contains(&term)
+}
+
+/// Write the prefix of every entry whose vendor matches `term`, each ending with `terminator` and
+/// undecorated so that the output can be fed to other tools.
+pub fn write_prefixes_for(
+    out: &mut impl Write,
+    entries: &[Oui],
+    term: &str,
+    terminator: &str,
+) -> io::Result<()> {
+    let mut last = None;
+    for oui in entries.iter().filter(|oui| vendor_matches(oui, term)) {
+        // conflicting duplicates are adjacent
+        if last != Some(oui.mac_prefix) {
+            write!(out, "{}{terminator}", oui.mac_prefix)?;
+        }
+        last = Some(oui.mac_prefix);
+    }
+    Ok(())
+}
+
+impl OutputArgs {
+    /// Open where results are written, the `--output` file or else stdout.
+    ///
+    /// Stdout is line-buffered, which is a syscall per result. When all queries are known up
+    /// front nobody is waiting on partial output and it can be fully buffered, but when
+    /// `streaming` from stdin each line is flushed as soon as it's written.
+    pub fn writer(&self, streaming: bool) -> anyhow::Result<Box<dyn Write>> {
+        let null = self.null;
+        let buffered = |out: Box<dyn Write>| -> Box<dyn Write> {
+            if null {
+                Box::new(NulLineWriter { inner: BufWriter::new(out), flush_each: streaming })
+            } else if streaming {
+                Box::new(LineWriter::new(out))
+            } else {
+                Box::new(BufWriter::new(out))
+            }
+        };
+        let Some(path) = &self.output else {
+            return Ok(buffered(Box::new(io::stdout().lock())));
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create directory {}", dir.display()))?;
+        }
+        let fp = File::create(path)
+            .with_context(|| format!("failed to open {} for writing", path.display()))?;
+        Ok(buffered(Box::new(fp)))
+    }
+
+    /// Get what ends each result record: a NUL with `--null`, otherwise a newline
+    pub fn terminator(&self) -> &'static str {
+        if self.null { "\0" } else { "\n" }
+    }
+
+    /// Get the sanitized long or short vendor name, depending on `--short`
+    pub fn name<'a>(&self, oui: &'a Oui) -> Cow<'a, str> {
+        sanitize_name(if self.short { oui.short_name() } else { oui.long_name() })
+    }
+
+    /// Is either JSON output mode selected?
+    fn is_json(&self) -> bool {
+        self.json || self.json_pretty
+    }
+
+    /// Is the default text output selected, rather than any of the alternate formats?
+    fn is_default_mode(&self) -> bool {
+        !(self.name_only
+            || self.format.is_some()
+            || self.is_json()
+            || self.group_by_vendor
+            || self.group_summary)
+    }
+
+    /// Should a result with this vendor be printed according to the vendor filters?
+    fn wants(&self, vendor: Option<&Oui>) -> bool {
+        let Some(oui) = vendor else {
+            return self.only_vendor.is_empty();
+        };
+        if self.misses_only {
+            return false;
+        }
+        let matches = |term: &String| vendor_matches(oui, term);
+        (self.only_vendor.is_empty() || self.only_vendor.iter().any(matches))
+            && !self.exclude_vendor.iter().any(matches)
+    }
+}
+
+/// Get the vendor to report for an address, which is never the raw database match for broadcast
+/// and locally administered addresses.
+pub fn reported_vendor(mac: MacAddress, found: Option<&Oui>) -> Option<&Oui> {
+    match mac.category() {
+        Category::Broadcast | Category::LocallyAdministered => None,
+        Category::VendorAssigned | Category::Multicast => found,
+    }
+}
+
+/// Write the lookup result for a single address as one line of JSON, with the same fields as an
+/// element of the `--json` array.
+pub fn write_json_line(
+    out: &mut impl Write,
+    mac: MacAddress,
+    found: Option<&Oui>,
+) -> io::Result<()> {
+    let record = JsonRecord::new(mac, Some(mac.category()), reported_vendor(mac, found));
+    serde_json::to_writer(&mut *out, &record)?;
+    writeln!(out)
+}
+
+/// A single result in JSON output.
+///
+/// Vendor names are not sanitized here, JSON escapes any control characters itself.
+#[derive(Debug, Serialize)]
+struct JsonRecord {
+    /// What the address belongs to, like the network interface for `--local`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    /// The queried address or wildcard prefix
+    mac: String,
+    /// The address category, absent for prefix listings
+    category: Option<Category>,
+    prefix: Option<String>,
+    short_name: Option<String>,
+    long_name: Option<String>,
+    /// Only known for IEEE CSV data, and left out otherwise
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    /// Only with `--show-source`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    /// Why an input entry couldn't be looked up, in which case the other fields are empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// Only with `--enrich`
+    #[serde(flatten)]
+    details: Option<PrefixDetails>,
+}
+
+/// More about the matching prefix, added to JSON results by `--enrich`. Each field is null when
+/// there's no match, and the registry is also null for prefix lengths which aren't an IEEE block
+/// size.
+#[derive(Debug, Serialize)]
+struct PrefixDetails {
+    registry: Option<Registry>,
+    prefix_len: Option<u8>,
+    range: Option<AddressRange>,
+}
+
+/// The first and last addresses covered by a prefix
+#[derive(Debug, Serialize)]
+struct AddressRange {
+    first: String,
+    last: String,
+}
+
+impl PrefixDetails {
+    fn new(vendor: Option<&Oui>) -> Self {
+        let prefix = vendor.map(|oui| oui.mac_prefix);
+        Self {
+            registry: prefix.and_then(MacPrefix::registry),
+            prefix_len: prefix.map(MacPrefix::prefix_len),
+            range: prefix.map(|prefix| AddressRange {
+                first: prefix.mac().to_string(),
+                last: prefix.last().to_string(),
+            }),
+        }
+    }
+}
+
+impl JsonRecord {
+    fn new(mac: impl Display, category: Option<Category>, vendor: Option<&Oui>) -> Self {
+        Self {
+            label: None,
+            mac: mac.to_string(),
+            category,
+            prefix: vendor.map(|oui| oui.mac_prefix.to_string()),
+            short_name: vendor.map(|oui| oui.short_name().to_string()),
+            long_name: vendor.map(|oui| oui.long_name().to_string()),
+            address: vendor.and_then(|oui| oui.address.clone()),
+            source: None,
+            error: None,
+            details: None,
+        }
+    }
+}
+
+/// Buffers `--null` output, flushing after each write containing a NUL with `flush_each`, as a
+/// `LineWriter` does for newlines.
+///
+/// Printer writes each record terminator separately, and sanitized names never contain NUL, so a
+/// flush happens at the end of each record.
+struct NulLineWriter<W: Write> {
+    inner: BufWriter<W>,
+    flush_each: bool,
+}
+
+impl<W: Write> Write for NulLineWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if self.flush_each && buf[..written].contains(&0) {
+            self.inner.flush()?;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Queries with the same vendor, for `--group-by-vendor` and `--group-summary`
+#[derive(Debug)]
+struct VendorGroup {
+    /// The normalized vendor name which identifies the group, or `None` for results with no vendor
+    key: Option<String>,
+    /// For `--group-summary`, the category of the addresses with no vendor, since each kind of
+    /// miss is counted separately
+    miss: Option<Category>,
+    /// The sanitized name of the first vendor in the group, to print
+    label: String,
+    /// The addresses or prefixes queried
+    members: Vec<String>,
+}
+
+/// Counts of the results printed, periodically reported for `--progress-every`
+struct Progress<'a> {
+    every: NonZeroUsize,
+    processed: usize,
+    matched: usize,
+    out: Box<dyn Write + 'a>,
+}
+
+impl Progress<'_> {
+    fn count(&mut self, matched: bool) -> io::Result<()> {
+        self.processed += 1;
+        self.matched += usize::from(matched);
+        if self.processed % self.every == 0 {
+            writeln!(self.out, "processed {} inputs, {} matched", self.processed, self.matched)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes lookup results in the format selected by the output arguments.
+///
+/// JSON output is a single array and grouped output needs every result, so in those modes results
+/// are collected and only written by `finish`, unless JSON results are written as they come with
+/// `with_json_lines`.
+pub struct Printer<'a, W: Write> {
+    out: W,
+    args: &'a OutputArgs,
+    json: Vec<JsonRecord>,
+    /// Write each JSON result on its own line right away, rather than collecting an array
+    json_lines: bool,
+    groups: Vec<VendorGroup>,
+    /// Whether to color the default text output
+    color: bool,
+    /// Written before each line of default text output
+    indent: &'a str,
+    progress: Option<Progress<'a>>,
+}
+
+impl<'a, W: Write> Printer<'a, W> {
+    pub fn new(out: W, args: &'a OutputArgs) -> Self {
+        Self {
+            out,
+            args,
+            json: Vec::new(),
+            json_lines: false,
+            groups: Vec::new(),
+            color: false,
+            indent: "",
+            progress: None,
+        }
+    }
+
+    /// Indent each line of the default text output, to set results apart from other text
+    pub fn with_indent(mut self, indent: &'a str) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Report how many inputs have been processed and how many had a vendor to `out` after every
+    /// `every` results, separately from the results themselves
+    pub fn with_progress(mut self, every: NonZeroUsize, out: impl Write + 'a) -> Self {
+        self.progress = Some(Progress { every, processed: 0, matched: 0, out: Box::new(out) });
+        self
+    }
+
+    /// Write JSON results as one object per line as soon as each is given, for streaming input,
+    /// rather than as an array once every result is in
+    pub fn with_json_lines(mut self, json_lines: bool) -> Self {
+        self.json_lines = json_lines;
+        self
+    }
+
+    /// Enable or disable colors in the default text output
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Get the style for some text, which is empty when colors are disabled
+    fn style(&self, color: AnsiColor) -> Style {
+        if self.color { Style::new().fg_color(Some(color.into())) } else { Style::new() }
+    }
+
+    /// Make a JSON result, with the prefix details if `--enrich` is given and the source with
+    /// `--show-source`
+    fn json_record(
+        &self,
+        mac: impl Display,
+        category: Option<Category>,
+        vendor: Option<&Oui>,
+    ) -> JsonRecord {
+        let mut record = JsonRecord::new(mac, category, vendor);
+        if self.args.enrich {
+            record.details = Some(PrefixDetails::new(vendor));
+        }
+        if self.args.show_source {
+            record.source = vendor.and_then(|oui| oui.source.as_deref()).map(String::from);
+        }
+        record
+    }
+
+    /// Write a JSON result as a line, or collect it for the array written by `finish`
+    fn push_json(&mut self, record: JsonRecord) -> io::Result<()> {
+        if !self.json_lines {
+            self.json.push(record);
+            return Ok(());
+        }
+        serde_json::to_writer(&mut self.out, &record)?;
+        self.end_record()
+    }
+
+    /// Add a query to its vendor's group, keeping groups in order of first appearance
+    fn group(&mut self, query: impl Display, vendor: Option<&Oui>, category: Option<Category>) {
+        let key = vendor.map(Oui::normalized_vendor);
+        let miss = if vendor.is_none() && self.args.group_summary {
+            category.filter(|&category| category != Category::VendorAssigned)
+        } else {
+            None
+        };
+        let index = match self.groups.iter().position(|g| g.key == key && g.miss == miss) {
+            Some(index) => index,
+            None => {
+                let label = match (vendor, miss) {
+                    (Some(oui), _) => self.args.name(oui),
+                    (None, Some(category)) => Cow::Owned(category.to_string()),
+                    (None, None) => Cow::Borrowed("unknown"),
+                };
+                self.groups.push(VendorGroup {
+                    key,
+                    miss,
+                    label: label.into(),
+                    members: Vec::new(),
+                });
+                self.groups.len() - 1
+            }
+        };
+        self.groups[index].members.push(query.to_string());
+    }
+
+    /// Would the result for an address with database match `found` be written, or is it filtered
+    /// out?
+    pub fn shows(&self, mac: MacAddress, found: Option<&Oui>) -> bool {
+        let args = self.args;
+        args.wants(reported_vendor(mac, found))
+            && !(args.skip_local
+                && matches!(mac.category(), Category::Broadcast | Category::LocallyAdministered))
+    }
+
+    /// Write a line of input text, e.g. the log line an address was found in, sanitized and
+    /// without a vendor lookup
+    pub fn echo(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.out, "{}", sanitize_line(line))
+    }
+
+    /// End a record of output, which may span several lines in the default text output
+    fn end_record(&mut self) -> io::Result<()> {
+        self.out.write_all(self.args.terminator().as_bytes())
+    }
+
+    /// Write the lookup result for a single MAC address.
+    ///
+    /// `found` is the raw database match, which is not reported as the vendor for broadcast and
+    /// locally administered addresses. Nothing is written if the vendor is filtered out.
+    pub fn result(&mut self, mac: MacAddress, found: Option<&Oui>) -> io::Result<()> {
+        self.write_result(None, mac, found)
+    }
+
+    /// Write the lookup result for an address along with what it belongs to, like a network
+    /// interface name.
+    ///
+    /// The label starts the line of default text output, is a field of JSON output, and is shown
+    /// with the address in grouped output. Templates and `--name-only` leave it out.
+    pub fn labeled_result(
+        &mut self,
+        label: &str,
+        mac: MacAddress,
+        found: Option<&Oui>,
+    ) -> io::Result<()> {
+        self.write_result(Some(label), mac, found)
+    }
+
+    fn write_result(
+        &mut self,
+        label: Option<&str>,
+        mac: MacAddress,
+        found: Option<&Oui>,
+    ) -> io::Result<()> {
+        let args = self.args;
+        let category = mac.category();
+        let vendor = reported_vendor(mac, found);
+        if let Some(progress) = &mut self.progress {
+            progress.count(vendor.is_some())?;
+        }
+        if !self.shows(mac, found) {
+            return Ok(());
+        }
+        if args.is_json() {
+            let mut record = self.json_record(mac, Some(category), vendor);
+            record.label = label.map(String::from);
+            return self.push_json(record);
+        }
+        if args.group_by_vendor || args.group_summary {
+            match label {
+                Some(label) => {
+                    let label = sanitize_name(label);
+                    self.group(format_args!("{label}: {mac}"), vendor, Some(category))
+                }
+                None => self.group(mac, vendor, Some(category)),
+            }
+            return Ok(());
+        }
+        if let Some(template) = &args.format {
+            template.render(&mut self.out, mac, vendor)?;
+            return self.end_record();
+        }
+        let name = vendor.map(|oui| args.name(oui));
+
+        if args.name_only {
+            write!(self.out, "{}", name.as_deref().unwrap_or(&args.placeholder))?;
+            return self.end_record();
+        }
+
+        let (green, red, yellow) = (
+            self.style(AnsiColor::Green),
+            self.style(AnsiColor::Red),
+            self.style(AnsiColor::Yellow),
+        );
+        let indent = self.indent;
+        let out = &mut self.out;
+        write!(out, "{indent}")?;
+        if let Some(label) = label {
+            write!(out, "{}: ", sanitize_name(label))?;
+        }
+        // detail lines start with a newline, so that the record terminator ends the last line
+        match (category, vendor.zip(name)) {
+            (_, Some((oui, name))) => {
+                write!(out, "{mac} - {} - {green}{name}{green:#}", oui.mac_prefix)?;
+                if let Some(address) = &oui.address
+                    && args.show_address
+                {
+                    write!(out, "\n{indent}    address: {}", sanitize_name(address))?;
+                }
+                if let Some(source) = &oui.source
+                    && args.show_source
+                {
+                    write!(out, "\n{indent}    source: {source}")?;
+                }
+            }
+            (Category::VendorAssigned, None) => {
+                write!(out, "{mac} - {red}no matching OUI found{red:#}")?
+            }
+            (Category::Multicast, None) => {
+                write!(out, "{mac} - {red}multicast, no matching OUI found{red:#}")?
+            }
+            (Category::LocallyAdministered, None) => {
+                write!(out, "{mac} - {yellow}{category}{yellow:#}")?
+            }
+            (cat, None) => write!(out, "{mac} - {cat}")?,
+        }
+        if args.raw {
+            write!(out, "\n{indent}    raw: mac {:#014x}", mac.to_u64())?;
+            if let Some(oui) = vendor {
+                write!(out, ", prefix {:#018x}", oui.mac_prefix.packed())?;
+            }
+        }
+        self.end_record()
+    }
+
+    /// Write an error for an input entry which couldn't be looked up
+    pub fn invalid(&mut self, invalid: &InvalidInput) -> io::Result<()> {
+        if let Some(progress) = &mut self.progress {
+            progress.count(false)?;
+        }
+        if self.args.is_json() {
+            let mut record = JsonRecord::new(&invalid.input, None, None);
+            record.error = Some(invalid.error.clone());
+            return self.push_json(record);
+        }
+        write!(self.out, "{} - {}", sanitize_name(&invalid.input), invalid.error)?;
+        self.end_record()
+    }
+
+    /// Write the nearest entries to an unmatched address, only in the default output mode
+    pub fn nearest(
+        &mut self,
+        mac: MacAddress,
+        before: Option<&Oui>,
+        after: Option<&Oui>,
+    ) -> io::Result<()> {
+        let args = self.args;
+        if !args.is_default_mode() || !args.wants(None) {
+            return Ok(());
+        }
+        if let Category::Broadcast | Category::LocallyAdministered = mac.category() {
+            return Ok(());
+        }
+
+        for (label, oui) in [("before", before), ("after", after)] {
+            if let Some(oui) = oui {
+                let name = args.name(oui);
+                writeln!(self.out, "    nearest {label}: {} - {name}", oui.mac_prefix)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the distance from an address with vendor `found` to the next vendor's prefix, only in
+    /// the default output mode
+    pub fn boundary(
+        &mut self,
+        mac: MacAddress,
+        found: &Oui,
+        boundary: Option<(u64, &Oui)>,
+    ) -> io::Result<()> {
+        let args = self.args;
+        let vendor = reported_vendor(mac, Some(found));
+        if !args.is_default_mode() || vendor.is_none() || !args.wants(vendor) {
+            return Ok(());
+        }
+        match boundary {
+            Some((distance, next)) => writeln!(
+                self.out,
+                "    {distance} addresses to the next vendor: {} - {}",
+                next.mac_prefix,
+                args.name(next)
+            ),
+            None => writeln!(self.out, "    no other vendors after this address"),
+        }
+    }
+
+    /// Write the database entries found under a wildcard prefix, excluding filtered vendors
+    pub fn listing(&mut self, prefix: MacPrefix, entries: &[&Oui]) -> io::Result<()> {
+        let args = self.args;
+        let out = &mut self.out;
+        let entries =
+            entries.iter().copied().filter(|oui| args.wants(Some(oui))).collect::<Vec<_>>();
+        if entries.is_empty() && !args.wants(None) {
+            return Ok(());
+        }
+
+        if args.is_json() {
+            if entries.is_empty() {
+                return self.push_json(self.json_record(prefix, None, None));
+            }
+            for oui in &entries {
+                self.push_json(self.json_record(prefix, None, Some(oui)))?;
+            }
+            return Ok(());
+        }
+
+        if args.group_by_vendor || args.group_summary {
+            if entries.is_empty() {
+                self.group(prefix, None, None);
+            }
+            for oui in &entries {
+                self.group(prefix, Some(oui), None);
+            }
+            return Ok(());
+        }
+
+        let terminator = args.terminator();
+        if let Some(template) = &args.format {
+            if entries.is_empty() {
+                template.render(out, prefix, None)?;
+                return write!(out, "{terminator}");
+            }
+            for oui in &entries {
+                template.render(out, prefix, Some(oui))?;
+                write!(out, "{terminator}")?;
+            }
+            return Ok(());
+        }
+
+        if entries.is_empty() {
+            if args.name_only {
+                return write!(out, "{}{terminator}", args.placeholder);
+            }
+            return write!(out, "{prefix} - no matching OUI found{terminator}");
+        }
+
+        for oui in &entries {
+            let name = args.name(oui);
+            if args.name_only {
+                write!(out, "{name}{terminator}")?;
+            } else {
+                write!(out, "{prefix} - {} - {name}{terminator}", oui.mac_prefix)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the unregistered address ranges within a prefix
+    pub fn gaps(&mut self, parent: MacPrefix, gaps: &[(MacAddress, MacAddress)]) -> io::Result<()> {
+        let terminator = self.args.terminator();
+        if gaps.is_empty() {
+            return write!(self.out, "{parent} - fully covered{terminator}");
+        }
+        for (first, last) in gaps {
+            write!(self.out, "{parent} - unregistered: {first}-{last}{terminator}")?;
+        }
+        Ok(())
+    }
+
+    /// Write any collected JSON or grouped output and flush, returning the underlying writer
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.args.is_json() && !self.json_lines {
+            let json = if self.args.json_pretty {
+                serde_json::to_string_pretty(&self.json)
+            } else {
+                serde_json::to_string(&self.json)
+            };
+            write!(self.out, "{}", json.map_err(io::Error::other)?)?;
+            self.end_record()?;
+        }
+        // the unknown group goes last
+        self.groups.sort_by_key(|group| group.key.is_none());
+        if self.args.group_summary {
+            // stable, so ties stay in order of appearance with misses after vendors
+            self.groups.sort_by_key(|group| Reverse(group.members.len()));
+            let terminator = self.args.terminator();
+            for group in &self.groups {
+                write!(self.out, "{} {}{terminator}", group.members.len(), group.label)?;
+            }
+        } else {
+            for group in &self.groups {
+                writeln!(self.out, "{}", group.label)?;
+                for member in &group.members {
+                    writeln!(self.out, "    {member}")?;
+                }
+            }
+        }
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+
+    fn render(args: &OutputArgs, mac: &str, found: Option<&Oui>) -> String {
+        let mut printer = Printer::new(Vec::new(), args);
+        printer.result(MacAddress::parse(mac).unwrap(), found).unwrap();
+        String::from_utf8(printer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_name_only() {
+        let oui = Oui::from_manuf("00:50:F1           Maxlinear       Maxlinear, Inc").unwrap();
+        let mut args = OutputArgs { name_only: true, ..Default::default() };
+        assert_eq!(render(&args, "00:50:f1:12:34:56", Some(&oui)), "Maxlinear, Inc\n");
+        assert_eq!(render(&args, "00:11:22:33:44:55", None), "\n");
+        // locally administered addresses don't claim the vendor
+        assert_eq!(render(&args, "02:50:f1:12:34:56", Some(&oui)), "\n");
+
+        args.short = true;
+        args.placeholder = String::from("unknown");
+        assert_eq!(render(&args, "00:50:f1:12:34:56", Some(&oui)), "Maxlinear\n");
+        assert_eq!(render(&args, "00:11:22:33:44:55", None), "unknown\n");
+
+        args.name_only = false;
+        assert_eq!(
+            render(&args, "00:50:f1:12:34:56", Some(&oui)),
+            "00:50:f1:12:34:56 - 00:50:f1/24 - Maxlinear\n"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_name() {
+        assert!(matches!(sanitize_name("Maxlinear, Inc"), Cow::Borrowed("Maxlinear, Inc")));
+        assert_eq!(sanitize_name("Evil\nCorp"), "Evil\\nCorp");
+        assert_eq!(sanitize_name("Tab\tCorp"), "Tab\\tCorp");
+        assert_eq!(sanitize_name("\x1b[31mRed\x1b[0m Corp"), "\\u{1b}[31mRed\\u{1b}[0m Corp");
+        assert_eq!(sanitize_name("Ünïcödé GmbH"), "Ünïcödé GmbH");
+
+        let long = "x".repeat(1000);
+        assert_eq!(sanitize_name(&long), format!("{}...", &long[..MAX_NAME_LEN]));
+        // the limit counts characters, not bytes
+        let long = "ü".repeat(MAX_NAME_LEN);
+        assert!(matches!(sanitize_name(&long), Cow::Borrowed(_)));
+        let longer = format!("{long}ü");
+        assert_eq!(sanitize_name(&longer), format!("{long}..."));
+    }
+
+    #[test]
+    fn test_nearest() {
+        let before = Oui::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc").unwrap();
+        let after = Oui::from_manuf("00:50:F4\tSigmatek\tSIGMATEK GMBH & CO. KG").unwrap();
+        let mac = MacAddress::parse("00:50:f2:00:00:01").unwrap();
+        let args = OutputArgs { nearest: true, ..Default::default() };
+        let mut printer = Printer::new(Vec::new(), &args);
+        printer.nearest(mac, Some(&before), Some(&after)).unwrap();
+        assert_eq!(
+            String::from_utf8(printer.finish().unwrap()).unwrap(),
+            "    nearest before: 00:50:f1/24 - Maxlinear, Inc\n    \
+             nearest after: 00:50:f4/24 - SIGMATEK GMBH & CO. KG\n"
+        );
+    }
+
+    #[test]
+    fn test_vendor_filters() {
+        let maxlinear = Oui::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc").unwrap();
+        let nanoleaf = Oui::from_manuf("00:55:DA:50/28\tNanoleaf\tNanoleaf").unwrap();
+        let cisco = Oui::from_manuf("00:00:0C\tCisco\tCisco Systems, Inc").unwrap();
+        let results = [
+            ("00:50:f1:12:34:56", Some(&maxlinear)),
+            ("00:55:da:50:00:01", Some(&nanoleaf)),
+            ("00:00:0c:12:34:56", Some(&cisco)),
+            ("00:11:22:33:44:55", None),
+        ];
+        let render_all = |args: &OutputArgs| {
+            results.iter().map(|(mac, found)| render(args, mac, *found)).collect::<String>()
+        };
+
+        let args = OutputArgs {
+            name_only: true,
+            only_vendor: vec![String::from("INC"), String::from("nanoleaf")],
+            exclude_vendor: vec![String::from("cisco")],
+            ..Default::default()
+        };
+        assert_eq!(render_all(&args), "Maxlinear, Inc\nNanoleaf\n");
+
+        let args = OutputArgs {
+            name_only: true,
+            exclude_vendor: vec![String::from("maxlinear")],
+            ..Default::default()
+        };
+        assert_eq!(render_all(&args), "Nanoleaf\nCisco Systems, Inc\n\n");
+    }
+
+    #[test]
+    fn test_json_pretty() {
+        let oui = Oui::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc").unwrap();
+        let args = OutputArgs { json: true, ..Default::default() };
+        assert_eq!(
+            render(&args, "00:50:f1:12:34:56", Some(&oui)),
+            "[{\"mac\":\"00:50:f1:12:34:56\",\"category\":\"vendor-assigned\",\
+             \"prefix\":\"00:50:f1/24\",\"short_name\":\"Maxlinear\",\
+             \"long_name\":\"Maxlinear, Inc\"}]\n"
+        );
+
+        let args = OutputArgs { json_pretty: true, ..Default::default() };
+        assert_eq!(
+            render(&args, "00:50:f1:12:34:56", Some(&oui)),
+            r#"[
+  {
+    "mac": "00:50:f1:12:34:56",
+    "category": "vendor-assigned",
+    "prefix": "00:50:f1/24",
+    "short_name": "Maxlinear",
+    "long_name": "Maxlinear, Inc"
+  }
+]
+"#
+        );
+    }
+
+    #[test]
+    fn test_prefixes_for() {
+        let db = oui_lookup::database::OuiDatabase::from_manuf(
+            "\
+00:03:93\tApple\tApple, Inc.
+00:05:02\tApple\tApple, Inc.
+00:05:02\tApple\tApple Computer
+00:50:F1\tMaxlinear\tMaxlinear, Inc
+70:B3:D5:F2:A0/36\tApple\tApple Pie Systems
+",
+        );
+        let mut out = Vec::new();
+        write_prefixes_for(&mut out, db.entries(), "APPLE", "\n").unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out, "00:03:93/24\n00:05:02/24\n70:b3:d5:f2:a0/36\n");
+        for line in out.lines() {
+            let prefix = MacPrefix::parse(line).unwrap();
+            assert_eq!(prefix.to_string(), line);
+            assert!(db.lookup(prefix.mac()).is_some());
+        }
+    }
+
+    #[test]
+    fn test_misses_only() {
+        let maxlinear = Oui::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc").unwrap();
+        let results = [
+            ("00:50:f1:12:34:56", Some(&maxlinear)),
+            ("00:11:22:33:44:55", None),
+            ("02:50:f1:12:34:56", Some(&maxlinear)),
+            ("ff:ff:ff:ff:ff:ff", None),
+        ];
+        let render_all = |args: &OutputArgs| {
+            results.iter().map(|(mac, found)| render(args, mac, *found)).collect::<String>()
+        };
+
+        let mut args = OutputArgs { misses_only: true, ..Default::default() };
+        assert_eq!(
+            render_all(&args),
+            "\
+00:11:22:33:44:55 - no matching OUI found
+02:50:f1:12:34:56 - locally-administered (likely randomized)
+ff:ff:ff:ff:ff:ff - broadcast
+"
+        );
+        args.skip_local = true;
+        assert_eq!(render_all(&args), "00:11:22:33:44:55 - no matching OUI found\n");
+    }
+
+    #[test]
+    fn test_group_by_vendor() {
+        let maxlinear = Oui::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc").unwrap();
+        // the same vendor written differently
+        let maxlinear2 = Oui::from_manuf("00:50:F2\tMaxlinear\tMaxlinear").unwrap();
+        let args = OutputArgs { group_by_vendor: true, ..Default::default() };
+        let mut printer = Printer::new(Vec::new(), &args);
+        for (mac, found) in [
+            ("00:11:22:33:44:55", None),
+            ("00:50:f1:12:34:56", Some(&maxlinear)),
+            ("00:50:f2:00:00:01", Some(&maxlinear2)),
+        ] {
+            printer.result(MacAddress::parse(mac).unwrap(), found).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(printer.finish().unwrap()).unwrap(),
+            "Maxlinear, Inc\n    00:50:f1:12:34:56\n    00:50:f2:00:00:01\nunknown\n    \
+             00:11:22:33:44:55\n"
+        );
+    }
+
+    #[test]
+    fn test_group_summary() {
+        let maxlinear = Oui::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc").unwrap();
+        let maxlinear2 = Oui::from_manuf("00:50:F2\tMaxlinear\tMaxlinear").unwrap();
+        let cisco = Oui::from_manuf("00:00:0C\tCisco\tCisco Systems, Inc").unwrap();
+        let args = OutputArgs { group_summary: true, ..Default::default() };
+        let mut printer = Printer::new(Vec::new(), &args);
+        for (mac, found) in [
+            ("00:00:0c:00:00:01", Some(&cisco)),
+            ("00:11:22:33:44:55", None),
+            ("00:50:f1:12:34:56", Some(&maxlinear)),
+            ("02:11:22:33:44:55", None),
+            ("00:50:f2:00:00:01", Some(&maxlinear2)),
+            ("00:11:22:33:44:56", None),
+            ("00:50:f1:00:00:02", Some(&maxlinear)),
+            ("ff:ff:ff:ff:ff:ff", None),
+            ("02:50:f1:00:00:01", Some(&maxlinear)),
+            ("00:00:0c:00:00:02", Some(&cisco)),
+        ] {
+            printer.result(MacAddress::parse(mac).unwrap(), found).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(printer.finish().unwrap()).unwrap(),
+            "3 Maxlinear, Inc\n\
+             2 Cisco Systems, Inc\n\
+             2 unknown\n\
+             2 locally-administered (likely randomized)\n\
+             1 broadcast\n"
+        );
+    }
+
+    #[test]
+    fn test_color() {
+        let oui = Oui::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc").unwrap();
+        let results = [
+            ("00:50:f1:12:34:56", Some(&oui)),
+            ("00:11:22:33:44:55", None),
+            ("02:11:22:33:44:55", None),
+            ("ff:ff:ff:ff:ff:ff", None),
+        ];
+        let render_all = |color: bool| {
+            let args = OutputArgs::default();
+            let mut printer = Printer::new(Vec::new(), &args).with_color(color);
+            for (mac, found) in results {
+                printer.result(MacAddress::parse(mac).unwrap(), found).unwrap();
+            }
+            String::from_utf8(printer.finish().unwrap()).unwrap()
+        };
+
+        let plain = render_all(false);
+        assert!(!plain.contains('\x1b'));
+        assert_eq!(plain.lines().count(), 4);
+
+        let colored = render_all(true);
+        assert!(colored.contains("\x1b[32mMaxlinear, Inc\x1b[0m"));
+        assert_ne!(colored, plain);
+    }
+
+    #[test]
+    fn test_show_address() {
+        let oui =
+            Oui::from_ieee_csv("MA-L,0050F1,\"Maxlinear, Inc\",Carlsbad CA US 92008").unwrap();
+        let mut args = OutputArgs { show_address: true, ..Default::default() };
+        assert_eq!(
+            render(&args, "00:50:f1:12:34:56", Some(&oui)),
+            "00:50:f1:12:34:56 - 00:50:f1/24 - Maxlinear, Inc\n    address: Carlsbad CA US 92008\n"
+        );
+
+        args.json = true;
+        let json = render(&args, "00:50:f1:12:34:56", Some(&oui));
+        let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(json[0]["address"], "Carlsbad CA US 92008");
+    }
+
+    #[test]
+    fn test_show_source() {
+        let mut oui = Oui::from_manuf("00:50:F1\tLab\tLab Equipment").unwrap();
+        oui.source = Some(Arc::from("override"));
+        let mut args = OutputArgs::default();
+        let text = "00:50:f1:12:34:56 - 00:50:f1/24 - Lab Equipment\n";
+        assert_eq!(render(&args, "00:50:f1:12:34:56", Some(&oui)), text);
+
+        args.show_source = true;
+        assert_eq!(
+            render(&args, "00:50:f1:12:34:56", Some(&oui)),
+            format!("{text}    source: override\n")
+        );
+
+        // the detail line is part of the same --null record
+        args.null = true;
+        assert_eq!(
+            render(&args, "00:50:f1:12:34:56", Some(&oui)),
+            "00:50:f1:12:34:56 - 00:50:f1/24 - Lab Equipment\n    source: override\0"
+        );
+
+        args.null = false;
+        args.json = true;
+        let json = render(&args, "00:50:f1:12:34:56", Some(&oui));
+        let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(json[0]["source"], "override");
+    }
+
+    #[test]
+    fn test_raw() {
+        let oui = Oui::from_manuf("00:55:DA:50/28\tNanoleaf\tNanoleaf").unwrap();
+        let args = OutputArgs { raw: true, ..Default::default() };
+        assert_eq!(
+            render(&args, "00:55:da:5f:a1:23", Some(&oui)),
+            "00:55:da:5f:a1:23 - 00:55:da:50/28 - Nanoleaf\n    \
+             raw: mac 0x0055da5fa123, prefix 0x1c000055da500000\n"
+        );
+        assert_eq!(
+            render(&args, "00:11:22:33:44:55", None),
+            "00:11:22:33:44:55 - no matching OUI found\n    raw: mac 0x001122334455\n"
+        );
+    }
+
+    #[test]
+    fn test_enrich() {
+        let oui = Oui::from_manuf("00:55:DA:50/28\tNanoleaf\tNanoleaf").unwrap();
+        let args = OutputArgs { json: true, enrich: true, ..Default::default() };
+        let json = render(&args, "00:55:da:5f:a1:23", Some(&oui));
+        let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        let mut keys = json[0].as_object().unwrap().keys().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "category",
+                "long_name",
+                "mac",
+                "prefix",
+                "prefix_len",
+                "range",
+                "registry",
+                "short_name"
+            ]
+        );
+        assert_eq!(json[0]["category"], "vendor-assigned");
+        assert_eq!(json[0]["registry"], "MA-M");
+        assert_eq!(json[0]["prefix_len"], 28);
+        assert_eq!(json[0]["range"]["first"], "00:55:da:50:00:00");
+        assert_eq!(json[0]["range"]["last"], "00:55:da:5f:ff:ff");
+
+        let json = render(&args, "00:11:22:33:44:55", None);
+        let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert!(json[0]["registry"].is_null());
+        assert!(json[0]["range"].is_null());
+        assert!(json[0].get("prefix_len").is_some());
+
+        // nothing extra without --enrich
+        let args = OutputArgs { json: true, ..Default::default() };
+        let json = render(&args, "00:55:da:5f:a1:23", Some(&oui));
+        assert!(!json.contains("registry"));
+    }
+
+    #[test]
+    fn test_progress() {
+        let oui = Oui::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc").unwrap();
+        let args = OutputArgs::default();
+        let mut progress = Vec::new();
+        let mut printer = Printer::new(Vec::new(), &args)
+            .with_progress(NonZeroUsize::new(2).unwrap(), &mut progress);
+        for (mac, found) in [
+            ("00:50:f1:00:00:01", Some(&oui)),
+            ("00:11:22:33:44:55", None),
+            ("00:50:f1:00:00:02", Some(&oui)),
+        ] {
+            printer.result(MacAddress::parse(mac).unwrap(), found).unwrap();
+        }
+        let invalid = InvalidInput { input: String::from("nope"), error: String::from("invalid") };
+        printer.invalid(&invalid).unwrap();
+        let out = String::from_utf8(printer.finish().unwrap()).unwrap();
+        assert_eq!(out.lines().count(), 4);
+        assert_eq!(
+            String::from_utf8(progress).unwrap(),
+            "processed 2 inputs, 1 matched\nprocessed 4 inputs, 2 matched\n"
+        );
+    }
+
+    #[test]
+    fn test_output_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("results/lookup.json");
+        let args = OutputArgs { output: Some(path.clone()), json: true, ..Default::default() };
+        let oui = Oui::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc").unwrap();
+        let mut printer = Printer::new(args.writer(false).unwrap(), &args);
+        printer.result(MacAddress::parse("00:50:f1:12:34:56").unwrap(), Some(&oui)).unwrap();
+        drop(printer.finish().unwrap());
+
+        let json = std::fs::read_to_string(&path).unwrap();
+        let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(json[0]["long_name"], "Maxlinear, Inc");
+
+        // a path under a regular file can't be created
+        let args = OutputArgs { output: Some(path.join("nope")), ..Default::default() };
+        let err = args.writer(false).err().unwrap();
+        assert!(err.to_string().starts_with("failed to create directory"), "{err}");
+    }
+
+    #[test]
+    fn test_boundary() {
+        let oui = Oui::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc").unwrap();
+        let next = Oui::from_manuf("00:50:F2\tMicrosoft\tMicrosoft").unwrap();
+        let args = OutputArgs::default();
+        let mut printer = Printer::new(Vec::new(), &args);
+        let mac = MacAddress::parse("00:50:f1:ff:ff:00").unwrap();
+        printer.boundary(mac, &oui, Some((256, &next))).unwrap();
+        printer.boundary(mac, &oui, None).unwrap();
+        // locally administered addresses don't claim the vendor
+        printer.boundary(MacAddress::parse("02:50:f1:ff:ff:00").unwrap(), &oui, None).unwrap();
+        assert_eq!(
+            String::from_utf8(printer.finish().unwrap()).unwrap(),
+            "    256 addresses to the next vendor: 00:50:f2/24 - Microsoft\n\
+             \x20   no other vendors after this address\n"
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Context as _;
+use serde::Deserialize;
+
+use oui_lookup::database::OuiDatabase;
+use oui_lookup::oui::MacPrefix;
+
+/// Corrected vendor names, read from a TOML file like
+///
+/// ```toml
+/// [vendors]
+/// "Old Company, Inc" = "New Company, Inc"
+///
+/// [prefixes]
+/// "00:50:f1" = "Some Other Name"
+/// "00:1b:21" = { long = "Intel Corporation", short = "Intel" }
+/// ```
+///
+/// A plain string is a new long name, and short names are only changed when given in a table.
+/// Vendor renames apply to every entry with that exact long name. Prefix renames apply to the
+/// entries with exactly that prefix, and take precedence. Prefixes without a `/N` length are
+/// /24, so a full address like `"00:50:f1:12:34:56"` means its MA-L prefix.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Renames {
+    vendors: HashMap<String, NewName>,
+    prefixes: HashMap<String, NewName>,
+}
+
+/// The names to give renamed entries
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum NewName {
+    Long(String),
+    Names(Names),
+}
+
+/// New long and short names, either of which may be left out to keep the old one
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Names {
+    long: Option<String>,
+    short: Option<String>,
+}
+
+impl NewName {
+    fn long(&self) -> Option<&str> {
+        match self {
+            Self::Long(long) => Some(long),
+            Self::Names(names) => names.long.as_deref(),
+        }
+    }
+
+    fn short(&self) -> Option<&str> {
+        match self {
+            Self::Long(_) => None,
+            Self::Names(names) => names.short.as_deref(),
+        }
+    }
+}
+
+impl Renames {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let data = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&data).with_context(|| format!("invalid rename file {}", path.display()))
+    }
+
+    /// Rename the vendors of the database's entries
+    pub fn apply(&self, db: &mut OuiDatabase) -> anyhow::Result<()> {
+        let prefixes = self
+            .prefixes
+            .iter()
+            .map(|(prefix, name)| {
+                let parsed = MacPrefix::parse(prefix)
+                    .with_context(|| format!("invalid MAC prefix '{prefix}' in rename file"))?;
+                Ok((parsed, name))
+            })
+            .collect::<anyhow::Result<HashMap<_, _>>>()?;
+        db.rename_vendors(|oui| {
+            match prefixes
+                .get(&oui.mac_prefix)
+                .copied()
+                .or_else(|| self.vendors.get(oui.long_name()))
+            {
+                Some(name) => (name.long(), name.short()),
+                None => (None, None),
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use oui_lookup::oui::MacAddress;
+
+    #[test]
+    fn test_rename() {
+        let mut db = OuiDatabase::from_manuf(
+            "\
+00:00:0C\tCisco\tCisco Systems, Inc
+00:1B:21\tIntelCor\tIntel Corporate
+00:50:F1\tMaxlinear\tMaxlinear, Inc
+00:50:F2\tMaxlinear\tMaxlinear, Inc
+",
+        );
+        let renames = toml::from_str::<Renames>(
+            "\
+[vendors]
+\"Maxlinear, Inc\" = \"MaxLinear, Inc.\"
+
+[prefixes]
+\"00:50:f2\" = \"Maxlinear Lab\"
+\"00:1b:21/24\" = { long = \"Intel Corporation\", short = \"Intel\" }
+\"00:00:0c\" = { short = \"CSCO\" }
+",
+        )
+        .unwrap();
+        renames.apply(&mut db).unwrap();
+
+        let name = |mac| db.lookup(MacAddress::parse(mac).unwrap()).unwrap().long_name();
+        assert_eq!(name("00:50:f1:12:34:56"), "MaxLinear, Inc.");
+        assert_eq!(name("00:50:f2:12:34:56"), "Maxlinear Lab");
+        assert_eq!(name("00:1b:21:12:34:56"), "Intel Corporation");
+        assert_eq!(name("00:00:0c:12:34:56"), "Cisco Systems, Inc");
+        // short names are only changed when the rename gives one
+        let short = |mac| db.lookup(MacAddress::parse(mac).unwrap()).unwrap().short_name();
+        assert_eq!(short("00:50:f1:12:34:56"), "Maxlinear");
+        assert_eq!(short("00:50:f2:12:34:56"), "Maxlinear");
+        assert_eq!(short("00:1b:21:12:34:56"), "Intel");
+        assert_eq!(short("00:00:0c:12:34:56"), "CSCO");
+
+        // prefixes without a length are /24, however many octets they have
+        let renames = toml::from_str::<Renames>(
+            "[prefixes]\n\"00:00:0c:12:34:56\" = \"Cisco\"\n\"00:1b:21:50\" = \"Intel\"\n",
+        )
+        .unwrap();
+        renames.apply(&mut db).unwrap();
+        let name = |mac| db.lookup(MacAddress::parse(mac).unwrap()).unwrap().long_name();
+        assert_eq!(name("00:00:0c:12:34:56"), "Cisco");
+        assert_eq!(name("00:1b:21:12:34:56"), "Intel");
+
+        assert!(toml::from_str::<Renames>("[vendors]\nx = { lon = \"y\" }\n").is_err());
+        let renames = toml::from_str::<Renames>("[prefixes]\n\"nope\" = \"x\"\n").unwrap();
+        assert!(renames.apply(&mut db).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use oui_lookup::database::OuiDatabase;
+use oui_lookup::oui::{Category, MacAddress, Oui};
+use oui_lookup::special;
+use oui_lookup::trie::TrieDatabase;
+use oui_lookup::wka::WellKnownAddresses;
+
+use crate::Backend;
+
+/// What was found for an address by [`Resolver::resolve_all`]
+pub struct Resolution<'a> {
+    /// The address the database entries matched, which with `--ignore-ig-bit` may be the query
+    /// with its I/G bit cleared
+    pub matched: MacAddress,
+    /// The database entries with the longest matching prefix, of which there's more than one when
+    /// the database has conflicting entries
+    pub candidates: &'a [Oui],
+    /// The vendor found from all the sources
+    pub found: Option<&'a Oui>,
+}
+
+/// Finds the vendor of an address the same way for every kind of input: the longest database
+/// match, else the most specific `--include-wka` entry, else a special prefix like IPv4 multicast.
+pub struct Resolver<'a> {
+    db: &'a OuiDatabase,
+    /// Built up front with `--backend trie`
+    trie: Option<TrieDatabase<'a>>,
+    wka: Option<&'a WellKnownAddresses>,
+    ignore_ig_bit: bool,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(db: &'a OuiDatabase) -> Self {
+        Self { db, trie: None, wka: None, ignore_ig_bit: false }
+    }
+
+    /// Also look up addresses in the entries of a wka file
+    pub fn with_wka(mut self, wka: Option<&'a WellKnownAddresses>) -> Self {
+        self.wka = wka;
+        self
+    }
+
+    /// Search the database with `backend`, building the trie now if it's needed
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.trie = (backend == Backend::Trie).then(|| self.db.build_trie());
+        self
+    }
+
+    /// Look up group addresses which don't match anything again with the I/G bit cleared, for
+    /// `--ignore-ig-bit`
+    pub fn with_ignore_ig_bit(mut self, ignore_ig_bit: bool) -> Self {
+        self.ignore_ig_bit = ignore_ig_bit;
+        self
+    }
+
+    /// Get the database being searched
+    pub fn db(&self) -> &'a OuiDatabase {
+        self.db
+    }
+
+    fn lookup_all(&self, mac: MacAddress) -> &'a [Oui] {
+        match &self.trie {
+            Some(trie) => trie.lookup_all(mac),
+            None => self.db.lookup_all(mac),
+        }
+    }
+
+    /// Find the vendor of an address along with the database entries and address it came from
+    pub fn resolve_all(&self, mac: MacAddress) -> Resolution<'a> {
+        let candidates = self.lookup_all(mac);
+        let found = candidates
+            .first()
+            .or_else(|| self.wka.and_then(|wka| wka.lookup(mac)))
+            .or_else(|| special::lookup(mac));
+        // only as a last resort, a group address with its own entry is better than a guess
+        if found.is_none() && self.ignore_ig_bit && mac.category() == Category::Multicast {
+            let matched = mac.to_individual();
+            let candidates = self.lookup_all(matched);
+            return Resolution { matched, candidates, found: candidates.first() };
+        }
+        Resolution { matched: mac, candidates, found }
+    }
+
+    /// Find the vendor of an address
+    pub fn resolve(&self, mac: MacAddress) -> Option<&'a Oui> {
+        self.resolve_all(mac).found
+    }
+}
@@ -0,0 +1,92 @@
+use std::io::Write;
+
+use oui_lookup::database::OuiDatabase;
+use oui_lookup::oui::MacAddress;
+
+use crate::CacheArgs;
+use crate::db;
+use crate::output::{sanitize_name, vendor_matches};
+
+/// Addresses from long-standing allocations which won't be reassigned, and a substring of the
+/// vendor name each should resolve to. Only substrings are checked, so the database can tidy up
+/// names without breaking this.
+const EXPECTED: [(&str, &str); 4] = [
+    ("00:00:0c:00:00:01", "cisco"),
+    ("00:03:93:00:00:01", "apple"),
+    ("00:50:56:00:00:01", "vmware"),
+    ("00:1b:21:00:00:01", "intel"),
+];
+
+/// Check that a database is well-formed and resolves the expected addresses, printing a line for
+/// each check. Returns the number of failed checks.
+fn check(db: &OuiDatabase, out: &mut impl Write) -> std::io::Result<usize> {
+    let mut failed = 0;
+    match db.check_invariants() {
+        Ok(()) => writeln!(out, "ok: {} entries are sorted and valid", db.len())?,
+        Err(err) => {
+            writeln!(out, "FAIL: {err}")?;
+            failed += 1;
+        }
+    }
+    for (mac, vendor) in EXPECTED {
+        let mac = MacAddress::parse(mac).unwrap();
+        match db.lookup(mac) {
+            Some(oui) if vendor_matches(oui, vendor) => {
+                writeln!(out, "ok: {mac} is {}", sanitize_name(oui.long_name()))?
+            }
+            Some(oui) => {
+                writeln!(
+                    out,
+                    "FAIL: {mac} is {}, expected {vendor}",
+                    sanitize_name(oui.long_name())
+                )?;
+                failed += 1;
+            }
+            None => {
+                writeln!(out, "FAIL: {mac} not found, expected {vendor}")?;
+                failed += 1;
+            }
+        }
+    }
+    Ok(failed)
+}
+
+/// Load the database and check that it's intact, failing if any check does
+pub fn run(cache_args: &CacheArgs) -> anyhow::Result<()> {
+    let db = db::load(cache_args)?;
+    let mut out = std::io::stdout().lock();
+    let failed = check(&db, &mut out)?;
+    if failed != 0 {
+        anyhow::bail!("{failed} self-test check(s) failed");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let good = OuiDatabase::from_manuf(
+            "\
+00:00:0C\tCisco\tCisco Systems, Inc
+00:03:93\tApple\tApple, Inc.
+00:1B:21\tIntelCor\tIntel Corporate
+00:50:56\tVMware\tVMware, Inc.
+",
+        );
+        let mut out = Vec::new();
+        assert_eq!(check(&good, &mut out).unwrap(), 0);
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), EXPECTED.len() + 1);
+        assert!(out.contains("ok: 00:03:93:00:00:01 is Apple, Inc.\n"));
+
+        let bad = OuiDatabase::from_manuf("00:00:0C\tCisco\tCisco Systems, Inc\n00:03:93\tX\tX\n");
+        let mut out = Vec::new();
+        assert_eq!(check(&bad, &mut out).unwrap(), 3);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("FAIL: 00:03:93:00:00:01 is X, expected apple\n"));
+        assert!(out.contains("FAIL: 00:50:56:00:00:01 not found, expected vmware\n"));
+    }
+}
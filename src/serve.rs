@@ -0,0 +1,485 @@
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::TcpListener;
+use std::num::NonZeroUsize;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use anyhow::Context as _;
+use lru::LruCache;
+use oui_lookup::oui::{MacAddress, Oui};
+use serif::macros::*;
+
+use crate::output::{self, Printer};
+use crate::resolve::Resolver;
+use crate::{OutputArgs, ServeArgs};
+
+/// Resolves addresses for every client, optionally remembering recent results.
+///
+/// Clients tend to ask about the same few addresses over and over, so a small LRU cache shared
+/// between connections saves repeating the search for each of them.
+struct CachedResolver<'a> {
+    resolver: Resolver<'a>,
+    cache: Option<Mutex<LruCache<MacAddress, Option<&'a Oui>>>>,
+    /// Number of lookups answered from the cache
+    hits: AtomicUsize,
+}
+
+impl<'a> CachedResolver<'a> {
+    fn new(resolver: Resolver<'a>, cache_size: Option<NonZeroUsize>) -> Self {
+        let cache = cache_size.map(|size| Mutex::new(LruCache::new(size)));
+        Self { resolver, cache, hits: AtomicUsize::new(0) }
+    }
+
+    fn lookup(&self, mac: MacAddress) -> Option<&'a Oui> {
+        let find = || self.resolver.resolve(mac);
+        let Some(cache) = &self.cache else {
+            return find();
+        };
+        if let Some(&found) = cache.lock().unwrap().get(&mac) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return found;
+        }
+        // don't hold the lock while searching, a duplicate search now and then is harmless
+        let found = find();
+        cache.lock().unwrap().put(mac, found);
+        found
+    }
+}
+
+/// Longest query line accepted, in bytes including the newline. Every address format is far
+/// shorter than this, so anything longer is garbage and the client is disconnected.
+const MAX_LINE: usize = 256;
+
+/// How long a client may go without sending anything before it's disconnected
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How each client connection is answered
+#[derive(Debug, Clone, Copy)]
+struct ClientOptions {
+    /// Reply with JSON objects rather than the default output format
+    json: bool,
+    /// Most queries answered on one connection before it's closed
+    max_lookups: Option<NonZeroUsize>,
+    /// Read timeout set on each client's socket
+    idle_timeout: Duration,
+}
+
+/// Write an error response line
+fn write_error(writer: &mut impl Write, json: bool, message: &str) -> io::Result<()> {
+    if json {
+        writeln!(writer, "{}", serde_json::json!({ "error": message }))
+    } else {
+        writeln!(writer, "error: {message}")
+    }
+}
+
+/// Answer newline-delimited queries from a client until it disconnects.
+///
+/// Each line gets exactly one response line, in the default output format or as a JSON object, or
+/// an error message for invalid addresses. Responses are flushed after every line. A client
+/// sending more than `max_lookups` queries or a line longer than `MAX_LINE` gets an error in place
+/// of the response, and is disconnected, as is one that's idle until the socket's read timeout.
+fn handle_client(
+    mut reader: impl BufRead,
+    mut writer: impl Write,
+    resolver: &CachedResolver,
+    options: ClientOptions,
+) -> io::Result<()> {
+    let ClientOptions { json, max_lookups, .. } = options;
+    let args = OutputArgs::default();
+    let mut line = Vec::new();
+    let mut lookups = 0;
+    loop {
+        line.clear();
+        match reader.by_ref().take(MAX_LINE as u64).read_until(b'\n', &mut line) {
+            Ok(0) => return Ok(()),
+            Ok(_) => (),
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                debug!("disconnecting idle client");
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        }
+        if line.len() == MAX_LINE && line.last() != Some(&b'\n') {
+            warn!("disconnecting client after a line longer than {MAX_LINE} bytes");
+            write_error(
+                &mut writer,
+                json,
+                &format!("line too long, the limit is {MAX_LINE} bytes"),
+            )?;
+            return writer.flush();
+        }
+        // invalid UTF-8 is just an invalid address, not a reason to drop the client
+        let line = String::from_utf8_lossy(&line);
+        let query = line.trim();
+        if query.is_empty() {
+            continue;
+        }
+        lookups += 1;
+        if let Some(max) = max_lookups
+            && lookups > max.get()
+        {
+            warn!("disconnecting client after {max} lookups");
+            write_error(&mut writer, json, &format!("too many lookups, the limit is {max}"))?;
+            return writer.flush();
+        }
+        match query.parse::<MacAddress>() {
+            Ok(mac) => {
+                let found = resolver.lookup(mac);
+                if json {
+                    output::write_json_line(&mut writer, mac, found)?;
+                } else {
+                    let mut printer = Printer::new(&mut writer, &args);
+                    printer.result(mac, found)?;
+                    printer.finish()?;
+                }
+            }
+            Err(_) => write_error(&mut writer, json, &format!("invalid MAC address '{query}'"))?,
+        }
+        writer.flush()?;
+    }
+}
+
+/// Serve clients on a Unix socket one at a time, forever
+#[cfg(unix)]
+fn serve_unix(listener: &UnixListener, resolver: &CachedResolver, options: ClientOptions) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!("failed to accept connection: {err}");
+                continue;
+            }
+        };
+        debug!("client connected");
+        if let Err(err) = stream.set_read_timeout(Some(options.idle_timeout)) {
+            warn!("failed to set client read timeout: {err}");
+            continue;
+        }
+        if let Err(err) = handle_client(BufReader::new(&stream), &stream, resolver, options) {
+            warn!("client connection failed: {err}");
+        }
+    }
+}
+
+/// Serve TCP clients concurrently, each on its own thread, forever.
+///
+/// Connections beyond `max_clients` at a time get an error line and are closed.
+fn serve_tcp(
+    listener: &TcpListener,
+    resolver: &CachedResolver,
+    options: ClientOptions,
+    max_clients: usize,
+) {
+    let active = AtomicUsize::new(0);
+    std::thread::scope(|scope| {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    warn!("failed to accept connection: {err}");
+                    continue;
+                }
+            };
+            if let Err(err) = stream.set_read_timeout(Some(options.idle_timeout)) {
+                warn!("failed to set client read timeout: {err}");
+                continue;
+            }
+            if active.fetch_add(1, Ordering::SeqCst) >= max_clients {
+                active.fetch_sub(1, Ordering::SeqCst);
+                warn!("rejecting connection, already serving {max_clients} clients");
+                let _ = writeln!(&stream, "error: too many clients");
+                continue;
+            }
+
+            let active = &active;
+            scope.spawn(move || {
+                debug!("client connected from {:?}", stream.peer_addr());
+                if let Err(err) = handle_client(BufReader::new(&stream), &stream, resolver, options)
+                {
+                    warn!("client connection failed: {err}");
+                }
+                active.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+}
+
+/// Load the database once, then answer lookups on a Unix socket or TCP
+pub fn run(args: &ServeArgs) -> anyhow::Result<()> {
+    let db = crate::db::load(&args.cache_args)?;
+    let resolver = CachedResolver::new(
+        Resolver::new(&db).with_ignore_ig_bit(args.ignore_ig_bit),
+        args.lookup_cache,
+    );
+    let options = ClientOptions {
+        json: args.json,
+        max_lookups: args.max_lookups,
+        idle_timeout: IDLE_TIMEOUT,
+    };
+    if let Some(addr) = &args.tcp {
+        let listener =
+            TcpListener::bind(addr).with_context(|| format!("failed to listen on {addr}"))?;
+        info!("listening on {}", listener.local_addr()?);
+        serve_tcp(&listener, &resolver, options, args.max_clients);
+        return Ok(());
+    }
+    match &args.socket {
+        Some(socket) => run_unix(&resolver, socket, options),
+        None => anyhow::bail!("no address to listen on"),
+    }
+}
+
+#[cfg(unix)]
+fn run_unix(
+    resolver: &CachedResolver,
+    socket: &Path,
+    options: ClientOptions,
+) -> anyhow::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    // a socket left behind by a previous run would make bind fail, but anything else at the path
+    // is most likely a typo and must not be deleted
+    match std::fs::symlink_metadata(socket) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            std::fs::remove_file(socket)
+                .with_context(|| format!("failed to remove {}", socket.display()))?;
+            debug!("removed stale socket {}", socket.display());
+        }
+        Ok(_) => anyhow::bail!("{} exists and is not a socket", socket.display()),
+        Err(err) if err.kind() == ErrorKind::NotFound => (),
+        Err(err) => return Err(err).context(format!("failed to check {}", socket.display())),
+    }
+    let listener = UnixListener::bind(socket)
+        .with_context(|| format!("failed to listen on {}", socket.display()))?;
+    info!("listening on {}", socket.display());
+    serve_unix(&listener, resolver, options);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn run_unix(
+    _resolver: &CachedResolver,
+    _socket: &Path,
+    _options: ClientOptions,
+) -> anyhow::Result<()> {
+    anyhow::bail!("Unix sockets aren't supported on this platform")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use oui_lookup::database::OuiDatabase;
+    use std::net::{Shutdown, TcpStream};
+
+    fn test_db() -> OuiDatabase {
+        OuiDatabase::from_manuf("00:50:F1\tMaxlinear\tMaxlinear, Inc\n")
+    }
+
+    fn options(json: bool, max_lookups: Option<usize>) -> ClientOptions {
+        ClientOptions {
+            json,
+            max_lookups: max_lookups.and_then(NonZeroUsize::new),
+            idle_timeout: IDLE_TIMEOUT,
+        }
+    }
+
+    #[test]
+    fn test_serve_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            serve_tcp(
+                &listener,
+                &CachedResolver::new(Resolver::new(&test_db()), None),
+                options(true, None),
+                4,
+            )
+        });
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"00:50:f1:12:34:56\n\xff\xfe\nff:ff:ff:ff:ff:ff\n").unwrap();
+        stream.shutdown(Shutdown::Write).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let lines = response
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["long_name"], "Maxlinear, Inc");
+        assert_eq!(lines[0]["prefix"], "00:50:f1/24");
+        assert!(lines[1]["error"].as_str().unwrap().starts_with("invalid MAC address"));
+        assert_eq!(lines[2]["category"], "broadcast");
+        assert!(lines[2]["long_name"].is_null());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_serve_unix() {
+        use std::os::unix::net::UnixStream;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("oui.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        std::thread::spawn(move || {
+            serve_unix(
+                &listener,
+                &CachedResolver::new(Resolver::new(&test_db()), None),
+                options(false, None),
+            )
+        });
+
+        let query = |request: &str| {
+            let mut stream = UnixStream::connect(&path).unwrap();
+            stream.write_all(request.as_bytes()).unwrap();
+            stream.shutdown(Shutdown::Write).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        assert_eq!(
+            query("00:50:f1:12:34:56\n\nbogus\n00:11:22:33:44:55\n"),
+            "00:50:f1:12:34:56 - 00:50:f1/24 - Maxlinear, Inc\n\
+             error: invalid MAC address 'bogus'\n\
+             00:11:22:33:44:55 - no matching OUI found\n"
+        );
+        // a second connection after the first closed
+        assert_eq!(
+            query("00-50-F1-00-00-01"),
+            "00:50:f1:00:00:01 - 00:50:f1/24 - Maxlinear, Inc\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket_path_not_socket() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("oui.conf");
+        std::fs::write(&path, "keep me").unwrap();
+        let db = test_db();
+        let err =
+            run_unix(&CachedResolver::new(Resolver::new(&db), None), &path, options(false, None))
+                .unwrap_err();
+        assert!(err.to_string().ends_with("exists and is not a socket"), "{err}");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+    }
+
+    #[test]
+    fn test_lookup_cache() {
+        let db = test_db();
+        let resolver = CachedResolver::new(Resolver::new(&db), NonZeroUsize::new(2));
+        let macs = ["00:50:f1:12:34:56", "00:11:22:33:44:55", "33:33:00:00:00:01"]
+            .map(|s| s.parse::<MacAddress>().unwrap());
+
+        let mut output = Vec::new();
+        let input = "00:50:f1:12:34:56\n00:50:f1:12:34:56\n00:11:22:33:44:55\n00:50:f1:12:34:56\n";
+        handle_client(input.as_bytes(), &mut output, &resolver, options(true, None)).unwrap();
+        assert_eq!(resolver.hits.load(Ordering::Relaxed), 2);
+        assert_eq!(String::from_utf8(output).unwrap().lines().count(), 4);
+
+        // misses and special addresses are cached too, and the oldest entry gets evicted
+        assert!(resolver.lookup(macs[1]).is_none());
+        assert_eq!(resolver.lookup(macs[2]).unwrap().short_name(), "IPv6mcast");
+        assert_eq!(resolver.hits.load(Ordering::Relaxed), 3);
+        assert_eq!(resolver.lookup(macs[0]).unwrap().short_name(), "Maxlinear");
+        assert_eq!(resolver.hits.load(Ordering::Relaxed), 3);
+
+        // without a cache every lookup searches the database
+        let resolver = CachedResolver::new(Resolver::new(&db), None);
+        resolver.lookup(macs[0]);
+        resolver.lookup(macs[0]);
+        assert_eq!(resolver.hits.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_ignore_ig_bit() {
+        let db = test_db();
+        let mac = "01:50:f1:00:00:01".parse::<MacAddress>().unwrap();
+        assert!(CachedResolver::new(Resolver::new(&db), None).lookup(mac).is_none());
+        let resolver = CachedResolver::new(Resolver::new(&db).with_ignore_ig_bit(true), None);
+        assert_eq!(resolver.lookup(mac).unwrap().short_name(), "Maxlinear");
+    }
+
+    #[test]
+    fn test_max_lookups() {
+        let db = test_db();
+        let resolver = CachedResolver::new(Resolver::new(&db), None);
+        let input = "00:50:f1:00:00:01\n\nbogus\n00:50:f1:00:00:02\n00:50:f1:00:00:03\n";
+
+        let mut output = Vec::new();
+        handle_client(input.as_bytes(), &mut output, &resolver, options(false, Some(2))).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "00:50:f1:00:00:01 - 00:50:f1/24 - Maxlinear, Inc\n\
+             error: invalid MAC address 'bogus'\n\
+             error: too many lookups, the limit is 2\n"
+        );
+
+        let mut output = Vec::new();
+        handle_client(input.as_bytes(), &mut output, &resolver, options(true, Some(3))).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let last = output.lines().last().unwrap();
+        assert_eq!(output.lines().count(), 4);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(last).unwrap()["error"],
+            "too many lookups, the limit is 3"
+        );
+
+        // exactly at the limit is fine
+        let mut output = Vec::new();
+        handle_client(input.as_bytes(), &mut output, &resolver, options(false, Some(4))).unwrap();
+        assert!(!String::from_utf8(output).unwrap().contains("too many"));
+    }
+
+    #[test]
+    fn test_long_line() {
+        let db = test_db();
+        let resolver = CachedResolver::new(Resolver::new(&db), None);
+        let input =
+            format!("00:50:f1:00:00:01\n{}\n00:50:f1:00:00:02\n", "0".repeat(10 * MAX_LINE));
+
+        let mut output = Vec::new();
+        handle_client(input.as_bytes(), &mut output, &resolver, options(false, None)).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "00:50:f1:00:00:01 - 00:50:f1/24 - Maxlinear, Inc\n\
+                 error: line too long, the limit is {MAX_LINE} bytes\n"
+            )
+        );
+
+        // a final line without a newline is fine, as long as it's short
+        let mut output = Vec::new();
+        handle_client(&b"00:50:f1:00:00:01"[..], &mut output, &resolver, options(true, None))
+            .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn test_idle_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let options =
+            ClientOptions { idle_timeout: Duration::from_millis(50), ..options(false, None) };
+        std::thread::spawn(move || {
+            serve_tcp(&listener, &CachedResolver::new(Resolver::new(&test_db()), None), options, 1)
+        });
+
+        // the idle client is dropped, freeing its slot for the next one
+        let mut idle = TcpStream::connect(addr).unwrap();
+        let mut response = String::new();
+        idle.read_to_string(&mut response).unwrap();
+        assert_eq!(response, "");
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"00:50:f1:12:34:56\n").unwrap();
+        stream.shutdown(Shutdown::Write).unwrap();
+        stream.read_to_string(&mut response).unwrap();
+        assert_eq!(response, "00:50:f1:12:34:56 - 00:50:f1/24 - Maxlinear, Inc\n");
+    }
+}
@@ -0,0 +1,94 @@
+//! Well-known reserved and multicast mapping prefixes, which aren't in the manuf database.
+
+use std::net::IpAddr;
+use std::sync::LazyLock;
+
+use crate::oui::{MacAddress, Oui};
+
+/// Special prefixes, in manuf format. Mostly from RFC 7042 and IEEE 802.1Q.
+const SPECIAL_MANUF: &str = "\
+00:00:5E:00:01/40\tVRRP\tVRRP virtual router (IPv4)
+00:00:5E:00:02/40\tVRRPv6\tVRRP virtual router (IPv6)
+00:00:5E:00:53/40\tDocument\tReserved for documentation (RFC 7042)
+01:00:5E/25\tIPv4mcast\tIPv4 multicast
+01:00:5E:90:10/40\tDocument\tReserved for multicast documentation (RFC 7042)
+01:80:C2:00:00/44\tIEEE802.1\tIEEE 802.1 link-local (STP, LLDP, LACP, etc.)
+33:33/16\tIPv6mcast\tIPv6 multicast
+CF:00:00\tPPP\tReserved for PPP (RFC 7042)
+";
+
+static SPECIAL: LazyLock<Vec<Oui>> = LazyLock::new(|| {
+    SPECIAL_MANUF
+        .lines()
+        .map(|line| Oui::from_manuf(line).expect("invalid special prefix"))
+        .collect()
+});
+
+/// Find the most specific well-known special prefix matching a MAC address.
+pub fn lookup(mac: MacAddress) -> Option<&'static Oui> {
+    SPECIAL.iter().filter(|oui| oui.mac_prefix.matches(mac)).max_by_key(|oui| oui.prefix_len())
+}
+
+/// Map a multicast IP address to the MAC address its packets are sent to, or `None` if it isn't
+/// multicast.
+///
+/// IPv4 groups map their low 23 bits into 01:00:5e:00:00:00 (RFC 1112), so 32 groups share each
+/// MAC. IPv6 groups map their low 32 bits into 33:33:00:00:00:00 (RFC 2464).
+pub fn multicast_mac(ip: IpAddr) -> Option<MacAddress> {
+    match ip {
+        IpAddr::V4(ip) if ip.is_multicast() => {
+            let [_, b, c, d] = ip.octets();
+            Some(MacAddress::from([0x01, 0x00, 0x5e, b & 0x7f, c, d]))
+        }
+        IpAddr::V6(ip) if ip.is_multicast() => {
+            let [.., a, b, c, d] = ip.octets();
+            Some(MacAddress::from([0x33, 0x33, a, b, c, d]))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn name(mac: &str) -> Option<&'static str> {
+        lookup(MacAddress::parse(mac).unwrap()).map(|oui| oui.long_name())
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(name("01:00:5e:00:00:fb"), Some("IPv4 multicast"));
+        assert_eq!(name("01:00:5e:7f:ff:ff"), Some("IPv4 multicast"));
+        assert_eq!(name("01:00:5e:80:00:00"), None);
+        assert_eq!(
+            name("01:00:5e:90:10:01"),
+            Some("Reserved for multicast documentation (RFC 7042)")
+        );
+        assert_eq!(name("33:33:00:00:00:01"), Some("IPv6 multicast"));
+        assert_eq!(name("33:33:ff:12:34:56"), Some("IPv6 multicast"));
+        assert_eq!(name("33:34:00:00:00:01"), None);
+        assert_eq!(
+            name("01:80:c2:00:00:0e"),
+            Some("IEEE 802.1 link-local (STP, LLDP, LACP, etc.)")
+        );
+        assert_eq!(name("00:50:f1:12:34:56"), None);
+    }
+
+    #[test]
+    fn test_multicast_mac() {
+        let mac = |ip: &str| multicast_mac(ip.parse().unwrap()).map(|mac| mac.to_string());
+        // mDNS
+        assert_eq!(mac("224.0.0.251").as_deref(), Some("01:00:5e:00:00:fb"));
+        // the high bit of the second octet is dropped
+        assert_eq!(mac("239.255.1.2").as_deref(), Some("01:00:5e:7f:01:02"));
+        // a solicited-node group
+        assert_eq!(mac("ff02::1:ff12:3456").as_deref(), Some("33:33:ff:12:34:56"));
+        assert_eq!(mac("ff02::fb").as_deref(), Some("33:33:00:00:00:fb"));
+        assert_eq!(mac("192.0.2.1"), None);
+        assert_eq!(mac("2001:db8::1"), None);
+
+        let mac = multicast_mac("ff02::1".parse().unwrap()).unwrap();
+        assert_eq!(lookup(mac).unwrap().long_name(), "IPv6 multicast");
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use oui_lookup::oui::Oui;
+
+use crate::output::sanitize_name;
+
+/// A user-provided output format like `"{mac},{long}"`.
+///
+/// Placeholders are replaced by fields of the lookup result, and `{{`/`}}` are literal braces.
+/// Fields which don't apply, like the vendor name when there's no match, are left empty.
+#[derive(Debug, Clone)]
+pub struct Template(Vec<Segment>);
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    Mac,
+    Prefix,
+    Short,
+    Long,
+    Len,
+    Registry,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "mac" => Self::Mac,
+            "prefix" => Self::Prefix,
+            "short" => Self::Short,
+            "long" => Self::Long,
+            "len" => Self::Len,
+            "registry" => Self::Registry,
+            _ => return None,
+        })
+    }
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = s;
+        while let Some(i) = rest.find(['{', '}']) {
+            literal.push_str(&rest[..i]);
+            let (brace, after) = rest[i..].split_at(1);
+            if let Some(after) = after.strip_prefix(brace) {
+                // escaped brace
+                literal.push_str(brace);
+                rest = after;
+                continue;
+            }
+            if brace == "}" {
+                return Err(String::from("unmatched '}' in format"));
+            }
+
+            let (name, after) = after.split_once('}').ok_or("unclosed '{' in format")?;
+            let field = Field::from_name(name)
+                .ok_or_else(|| format!("unknown placeholder '{{{name}}}'"))?;
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(std::mem::take(&mut literal)));
+            }
+            segments.push(Segment::Field(field));
+            rest = after;
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Self(segments))
+    }
+}
+
+impl Template {
+    /// Write one formatted result without a line ending, where `mac` is the query and `vendor` its
+    /// match
+    pub fn render(
+        &self,
+        out: &mut impl Write,
+        mac: impl fmt::Display,
+        vendor: Option<&Oui>,
+    ) -> io::Result<()> {
+        for segment in &self.0 {
+            let field = match segment {
+                Segment::Literal(s) => {
+                    out.write_all(s.as_bytes())?;
+                    continue;
+                }
+                Segment::Field(field) => *field,
+            };
+            match (field, vendor) {
+                (Field::Mac, _) => write!(out, "{mac}")?,
+                (Field::Prefix, Some(oui)) => write!(out, "{}", oui.mac_prefix)?,
+                (Field::Short, Some(oui)) => write!(out, "{}", sanitize_name(oui.short_name()))?,
+                (Field::Long, Some(oui)) => write!(out, "{}", sanitize_name(oui.long_name()))?,
+                (Field::Len, Some(oui)) => write!(out, "{}", oui.prefix_len())?,
+                (Field::Registry, Some(oui)) => {
+                    if let Some(registry) = oui.mac_prefix.registry() {
+                        write!(out, "{registry}")?;
+                    }
+                }
+                (_, None) => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use oui_lookup::oui::MacAddress;
+
+    fn render(template: &str, vendor: Option<&Oui>) -> String {
+        let template = template.parse::<Template>().unwrap();
+        let mac = MacAddress::parse("00:55:da:50:12:34").unwrap();
+        let mut out = Vec::new();
+        template.render(&mut out, mac, vendor).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_render() {
+        let oui = Oui::from_manuf("00:55:DA:50/28     Nanoleaf        Nanoleaf Inc").unwrap();
+        assert_eq!(render("{mac},{long}", Some(&oui)), "00:55:da:50:12:34,Nanoleaf Inc");
+        assert_eq!(
+            render("{{{short}}} {prefix} len={len} {registry}", Some(&oui)),
+            "{Nanoleaf} 00:55:da:50/28 len=28 MA-M"
+        );
+        assert_eq!(render("{mac},{long}", None), "00:55:da:50:12:34,");
+        assert_eq!(render("no placeholders", None), "no placeholders");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Template::from_str("{mac} {vendor}").unwrap_err(),
+            "unknown placeholder '{vendor}'"
+        );
+        assert_eq!(Template::from_str("{mac").unwrap_err(), "unclosed '{' in format");
+        assert_eq!(Template::from_str("mac}").unwrap_err(), "unmatched '}' in format");
+    }
+}
@@ -0,0 +1,133 @@
+//! A binary trie over the 48-bit MAC address space, as an alternative lookup backend.
+
+use crate::database::OuiDatabase;
+use crate::oui::{MacAddress, Oui};
+
+/// Index of a trie node. The root is never a child, so zero means "no child".
+type NodeIndex = u32;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Node {
+    /// Children for a 0 or 1 in the next address bit
+    children: [NodeIndex; 2],
+    /// Range of database entries with exactly this prefix, empty if there are none
+    entries: (u32, u32),
+}
+
+/// A binary trie of the entries in an [`OuiDatabase`], storing each entry at the depth of its
+/// prefix length.
+///
+/// Lookups walk at most 48 nodes and always find the longest matching prefix, regardless of how
+/// entries overlap.
+#[derive(Debug)]
+pub struct TrieDatabase<'a> {
+    entries: &'a [Oui],
+    nodes: Vec<Node>,
+}
+
+impl<'a> TrieDatabase<'a> {
+    /// Build a trie referencing the entries of a database
+    pub fn new(db: &'a OuiDatabase) -> Self {
+        let entries = db.entries();
+        let mut nodes = vec![Node::default()];
+        let mut start = 0;
+        // entries with the same prefix are adjacent, and stored as a single range
+        for run in entries.chunk_by(|a, b| a.mac_prefix == b.mac_prefix) {
+            let prefix = run[0].mac_prefix;
+            let bits = prefix.mac().to_u64();
+            let mut node = 0;
+            for depth in 0..prefix.prefix_len() {
+                let bit = ((bits >> (47 - depth)) & 1) as usize;
+                if nodes[node].children[bit] == 0 {
+                    nodes.push(Node::default());
+                    nodes[node].children[bit] = (nodes.len() - 1) as NodeIndex;
+                }
+                node = nodes[node].children[bit] as usize;
+            }
+            let end = start + run.len();
+            nodes[node].entries = (start as u32, end as u32);
+            start = end;
+        }
+        Self { entries, nodes }
+    }
+
+    /// Find the entry with the longest prefix matching a MAC address
+    pub fn lookup(&self, mac: MacAddress) -> Option<&'a Oui> {
+        self.lookup_all(mac).first()
+    }
+
+    /// Find all the entries with the longest prefix matching a MAC address, like
+    /// [`OuiDatabase::lookup_all`].
+    pub fn lookup_all(&self, mac: MacAddress) -> &'a [Oui] {
+        let bits = mac.to_u64();
+        let mut node = &self.nodes[0];
+        let mut found = node.entries;
+        for depth in 0..48 {
+            let child = node.children[((bits >> (47 - depth)) & 1) as usize];
+            if child == 0 {
+                break;
+            }
+            node = &self.nodes[child as usize];
+            if node.entries.0 != node.entries.1 {
+                found = node.entries;
+            }
+        }
+        &self.entries[found.0 as usize..found.1 as usize]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_backends_agree() {
+        let db = OuiDatabase::from_manuf(
+            "\
+00:55:DA\tIeeeRegi\tIEEE Registration Authority
+00:55:DA:00/28\tFirst\tFirst block
+00:55:DA:50/28\tNanoleaf\tNanoleaf
+00:55:DA:F0/28\tLast\tLast block
+00:55:DA:F0:80/36\tTiny\tTiny block
+00:50:F1\tMaxlinear\tMaxlinear, Inc
+00:50:F1\tOther\tSomebody Else
+00:50:F2\tMicrosoft\tMicrosoft
+00:50/16\tWide\tWide block
+",
+        );
+        let trie = TrieDatabase::new(&db);
+
+        let name = |mac: &str| {
+            let mac = MacAddress::parse(mac).unwrap();
+            let names =
+                |found: &[Oui]| found.iter().map(|oui| oui.short_name().to_string()).collect();
+            let (vec, trie): (Vec<_>, Vec<_>) =
+                (names(db.lookup_all(mac)), names(trie.lookup_all(mac)));
+            assert_eq!(vec, trie, "backends differ for {mac}");
+            vec.join(",")
+        };
+        assert_eq!(name("00:55:da:00:00:01"), "First");
+        assert_eq!(name("00:55:da:12:34:56"), "IeeeRegi");
+        assert_eq!(name("00:55:da:50:00:01"), "Nanoleaf");
+        assert_eq!(name("00:55:da:f0:00:01"), "Last");
+        assert_eq!(name("00:55:da:f0:80:01"), "Tiny");
+        assert_eq!(name("00:55:da:f0:90:01"), "Last");
+        assert_eq!(name("00:55:da:ef:ff:ff"), "IeeeRegi");
+        assert_eq!(name("00:55:da:ff:ff:ff"), "Last");
+        assert_eq!(name("00:50:f1:12:34:56"), "Maxlinear,Other");
+        assert_eq!(name("00:50:f2:12:34:56"), "Microsoft");
+        assert_eq!(name("00:50:f3:12:34:56"), "Wide");
+        assert_eq!(name("00:51:00:00:00:00"), "");
+        assert_eq!(name("ff:ff:ff:ff:ff:ff"), "");
+
+        // and exhaustively around every entry's boundaries
+        for oui in db.entries() {
+            for mac in [oui.mac(), oui.mac_prefix.last()] {
+                for offset in [-1i64, 0, 1] {
+                    let mac = MacAddress::from_u64((mac.to_u64() as i64 + offset) as u64);
+                    name(&mac.to_string());
+                }
+            }
+        }
+    }
+}
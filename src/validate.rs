@@ -0,0 +1,113 @@
+use std::fmt;
+use std::path::Path;
+
+use anyhow::Context as _;
+
+use crate::oui::Oui;
+
+/// A problem found in a manuf file
+#[derive(Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// 1-based line number
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Check the contents of a manuf file for unparseable lines and duplicate or overlapping prefixes.
+///
+/// Problems are returned sorted by line number.
+pub fn validate(data: &str) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut entries = Vec::new();
+    for (i, line) in data.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        match Oui::from_manuf(line) {
+            Some(oui) => entries.push((i + 1, oui)),
+            None => {
+                problems.push(Problem { line: i + 1, message: format!("invalid entry: {line}") })
+            }
+        }
+    }
+
+    // stable sort, so duplicates stay in file order
+    entries.sort_by(|a, b| a.1.cmp(&b.1));
+    for (i, (first_line, first)) in entries.iter().enumerate() {
+        // since entries are sorted, anything overlapping `first` immediately follows it
+        for (line, oui) in &entries[i + 1..] {
+            if !first.mac_prefix.overlaps(oui.mac_prefix) {
+                break;
+            }
+            let message = if first.mac_prefix == oui.mac_prefix {
+                format!("duplicate prefix {} (first defined on line {first_line})", oui.mac_prefix)
+            } else {
+                format!(
+                    "prefix {} overlaps {} on line {first_line}",
+                    oui.mac_prefix, first.mac_prefix
+                )
+            };
+            problems.push(Problem { line: *line, message });
+        }
+    }
+
+    problems.sort_by_key(|p| p.line);
+    problems
+}
+
+/// Validate a manuf file, printing any problems found.
+pub fn run(path: &Path) -> anyhow::Result<()> {
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let problems = validate(&data);
+    for problem in &problems {
+        println!("{}:{}: {}", path.display(), problem.line, problem.message);
+    }
+
+    if !problems.is_empty() {
+        anyhow::bail!("found {} problem(s) in {}", problems.len(), path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let data = "\
+# comment
+00:50:F1\tMaxlinear\tMaxlinear, Inc
+
+00:55:DA\tIeeeRegi\tIEEE Registration Authority
+00:55:DA:50/28\tNanoleaf\tNanoleaf
+this is not an entry
+00:50:F1\tOther\tSomebody Else
+";
+        let problems = validate(data);
+        assert_eq!(
+            problems,
+            [
+                Problem {
+                    line: 5,
+                    message: "prefix 00:55:da:50/28 overlaps 00:55:da/24 on line 4".into()
+                },
+                Problem { line: 6, message: "invalid entry: this is not an entry".into() },
+                Problem {
+                    line: 7,
+                    message: "duplicate prefix 00:50:f1/24 (first defined on line 2)".into()
+                },
+            ]
+        );
+
+        assert!(validate("00:50:F1\tMaxlinear\tMaxlinear, Inc\n").is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+use std::io::Write;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use oui_lookup::cache::parse_http_date;
+use serde::Serialize;
+
+use crate::{Source, db};
+
+#[derive(Debug, Serialize)]
+struct Version {
+    version: &'static str,
+    #[serde(flatten)]
+    info: db::Info,
+}
+
+impl Version {
+    fn new(cache_file: Option<&Path>, source: Source) -> Self {
+        Self { version: clap::crate_version!(), info: db::info(cache_file, source) }
+    }
+
+    fn write_text(&self, out: &mut impl Write, now: DateTime<Utc>) -> std::io::Result<()> {
+        writeln!(out, "{} {}", clap::crate_name!(), self.version)?;
+        writeln!(out, "database URL: {}", self.info.db_url)?;
+        match &self.info.cache_path {
+            Some(path) => writeln!(out, "cache file: {}", path.display())?,
+            None => writeln!(out, "cache file: unknown")?,
+        }
+
+        let Some(cache) = &self.info.cache else {
+            return writeln!(out, "cached database: none");
+        };
+        writeln!(out, "cached entries: {}", cache.entries)?;
+        let unknown = "unknown";
+        match cache.last_modified.as_deref() {
+            Some(date) => match parse_http_date(date) {
+                Some(modified) => {
+                    writeln!(out, "last modified: {date} ({})", relative_age(modified, now))?
+                }
+                None => writeln!(out, "last modified: {date}")?,
+            },
+            None => writeln!(out, "last modified: {unknown}")?,
+        }
+        writeln!(out, "generated: {}", cache.generated.as_deref().unwrap_or(unknown))?;
+        writeln!(out, "etag: {}", cache.etag.as_deref().unwrap_or(unknown))
+    }
+}
+
+/// Describe how long ago `then` was, like "3 days ago", rounding down to the largest whole unit
+fn relative_age(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let age = now - then;
+    let (count, unit) = if age.num_seconds() < 0 {
+        return String::from("in the future");
+    } else if age.num_minutes() < 1 {
+        return String::from("just now");
+    } else if age.num_hours() < 1 {
+        (age.num_minutes(), "minute")
+    } else if age.num_days() < 2 {
+        (age.num_hours(), "hour")
+    } else {
+        (age.num_days(), "day")
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{count} {unit}{plural} ago")
+}
+
+/// Print version and info about a database source, as text or JSON
+pub fn run(cache_file: Option<&Path>, source: Source, json: bool) -> anyhow::Result<()> {
+    let version = Version::new(cache_file, source);
+    let mut stdout = std::io::stdout().lock();
+    if json {
+        serde_json::to_writer(&mut stdout, &version)?;
+        writeln!(stdout)?;
+    } else {
+        version.write_text(&mut stdout, Utc::now())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_json_keys() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("manuf.db");
+        let json = serde_json::to_value(Version::new(Some(&path), Source::Wireshark)).unwrap();
+        let mut keys = json.as_object().unwrap().keys().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, ["cache", "cache_path", "db_url", "version"]);
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["cache_path"], path.to_str().unwrap());
+        assert!(json["cache"].is_null());
+
+        // each source has its own URL and default cache
+        let ieee = Version::new(None, Source::Ieee).info;
+        assert_eq!(ieee.db_url, oui_lookup::cache::IEEE_CSV_URL);
+        let wireshark = Version::new(None, Source::Wireshark).info;
+        assert_eq!(wireshark.db_url, oui_lookup::cache::DB_URL);
+        if let (Some(ieee), Some(wireshark)) = (ieee.cache_path, wireshark.cache_path) {
+            assert_eq!(ieee.file_name().unwrap(), "ieee.db");
+            assert_ne!(ieee, wireshark);
+        }
+    }
+
+    #[test]
+    fn test_relative_age() {
+        let now = parse_http_date("Sun, 15 Jun 2025 12:00:00 GMT").unwrap();
+        let age = |date: &str| relative_age(parse_http_date(date).unwrap(), now);
+        assert_eq!(age("Sun, 15 Jun 2025 12:00:00 GMT"), "just now");
+        assert_eq!(age("Sun, 15 Jun 2025 11:59:01 GMT"), "just now");
+        assert_eq!(age("Sun, 15 Jun 2025 11:59:00 GMT"), "1 minute ago");
+        assert_eq!(age("Sun, 15 Jun 2025 11:15:30 GMT"), "44 minutes ago");
+        assert_eq!(age("Sun, 15 Jun 2025 10:00:00 GMT"), "2 hours ago");
+        assert_eq!(age("Fri, 13 Jun 2025 12:00:01 GMT"), "47 hours ago");
+        assert_eq!(age("Thu, 12 Jun 2025 09:00:00 GMT"), "3 days ago");
+        assert_eq!(age("Wed, 01 Jan 2025 00:00:00 GMT"), "165 days ago");
+        assert_eq!(age("Mon, 16 Jun 2025 12:00:00 GMT"), "in the future");
+    }
+
+    #[test]
+    fn test_write_text() {
+        let version = Version {
+            version: "1.2.3",
+            info: db::Info {
+                db_url: "https://example.com/manuf.gz",
+                cache_path: None,
+                cache: Some(db::CacheInfo {
+                    last_modified: Some(String::from("Thu, 12 Jun 2025 09:00:00 GMT")),
+                    etag: None,
+                    generated: None,
+                    entries: 10,
+                }),
+            },
+        };
+        let now = parse_http_date("Sun, 15 Jun 2025 12:00:00 GMT").unwrap();
+        let mut out = Vec::new();
+        version.write_text(&mut out, now).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("\nlast modified: Thu, 12 Jun 2025 09:00:00 GMT (3 days ago)\n"));
+    }
+}
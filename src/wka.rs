@@ -0,0 +1,75 @@
+//! Wireshark's `wka` file of well-known addresses, mostly multicast and protocol addresses with
+//! longer prefixes than the manuf database has.
+
+use crate::oui::{MacAddress, Oui};
+
+/// Where Wireshark installs the wka file on most Linux distributions
+pub const DEFAULT_WKA_PATH: &str = "/usr/share/wireshark/wka";
+
+/// Entries from a wka file.
+///
+/// Unlike the manuf database, entries here are often nested (a whole block plus specific addresses
+/// within it), and there are only a few hundred, so lookups are a linear longest-match search.
+#[derive(Debug, Default)]
+pub struct WellKnownAddresses {
+    entries: Vec<Oui>,
+}
+
+impl WellKnownAddresses {
+    /// Parse the contents of a wka file, skipping any lines which don't parse
+    pub fn from_wka(data: &str) -> Self {
+        Self { entries: data.lines().filter_map(Oui::from_wka).collect() }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Find the most specific entry matching a MAC address
+    pub fn lookup(&self, mac: MacAddress) -> Option<&Oui> {
+        self.entries
+            .iter()
+            .filter(|oui| oui.mac_prefix.matches(mac))
+            .max_by_key(|oui| oui.prefix_len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wka() {
+        let wka = WellKnownAddresses::from_wka(
+            "\
+# Well-known addresses.
+01-80-C2-00-00-00\tSpanning-tree-(for-bridges)_00
+01-80-C2-00-00-0E\tLLDP_Multicast   # 802.1AB
+01-80-C2-00-00-30/45\tCFM-Multicast
+01:00:0c:cc:cc:cc\tCDP/VTP/DTP/PAgP/UDLD
+not-an-address\tNope
+",
+        );
+        assert_eq!(wka.len(), 4);
+
+        let name =
+            |mac: &str| wka.lookup(MacAddress::parse(mac).unwrap()).map(|oui| oui.long_name());
+        assert_eq!(name("01:80:c2:00:00:00"), Some("Spanning-tree-(for-bridges)_00"));
+        assert_eq!(name("01:80:c2:00:00:0e"), Some("LLDP_Multicast"));
+        assert_eq!(name("01:80:c2:00:00:01"), None);
+        assert_eq!(name("01:80:c2:00:00:37"), Some("CFM-Multicast"));
+        assert_eq!(name("01:80:c2:00:00:38"), None);
+        assert_eq!(name("01:00:0c:cc:cc:cc"), Some("CDP/VTP/DTP/PAgP/UDLD"));
+
+        let oui = Oui::from_wka("01-80-C2-00-00-0E\tLLDP_Multicast").unwrap();
+        assert_eq!(oui.prefix_len(), 48);
+        assert_eq!(oui.short_name(), "LLDP_Multicast");
+        assert!(Oui::from_wka("01-80-C2-00-00-0E").is_none());
+    }
+}